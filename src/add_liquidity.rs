@@ -1,44 +1,71 @@
 use anchor_client::anchor_lang::AnchorSerialize;
-use solana_client::rpc_config::RpcSendTransactionConfig;
 
+use crate::amount::{resolve_mint_decimals, Amount};
+use crate::memo::Annotation;
+use crate::token_program::{resolve_token_program, TokenProgram};
+use crate::transaction::{execute_transaction, TransactionConfig, TransactionReport};
 use crate::utils::get_function_hash;
 
-use {
-    solana_client::nonblocking::rpc_client::RpcClient,
-    solana_sdk::{
-        instruction::Instruction, message::Message, signature::Signature, transaction::Transaction,
-    },
-};
+use {solana_client::nonblocking::rpc_client::RpcClient, solana_sdk::instruction::Instruction};
 
 use solana_sdk::{instruction::AccountMeta, signer::Signer, system_program};
 
 use solana_program::pubkey::Pubkey;
-use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
 
 use bridge_program::instruction as bridge_program_instructions;
 
+#[allow(clippy::too_many_arguments)]
 pub async fn process_add_liquidity(
     rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
     signer: &dyn Signer,
+    fee_payer: &dyn Signer,
     program_id: Pubkey,
-    amount: u64,
+    amount: &Amount,
     mint_of_token_sent: Pubkey,
-) -> Result<Signature, Box<dyn std::error::Error>> {
+    token_program: Option<TokenProgram>,
+    mint_decimals: Option<u8>,
+    annotation: &Annotation,
+) -> Result<TransactionReport, Box<dyn std::error::Error>> {
+    let token_program =
+        resolve_token_program(rpc_client, token_program, &mint_of_token_sent).await?;
+    let decimals = resolve_mint_decimals(
+        rpc_client,
+        mint_decimals,
+        tx_config.sign_only,
+        &mint_of_token_sent,
+    )
+    .await?;
+    let amount = amount
+        .to_token_amount(decimals)
+        .map_err(|err| format!("error: {err}"))?;
+
     let (bridge_pda, _) = Pubkey::find_program_address(&[b"bridge_state"], &program_id);
 
-    let vault_ata = get_associated_token_address(&bridge_pda, &mint_of_token_sent);
+    let vault_ata = get_associated_token_address_with_program_id(
+        &bridge_pda,
+        &mint_of_token_sent,
+        &token_program,
+    );
 
-    let signer_ata = get_associated_token_address(&signer.pubkey(), &mint_of_token_sent);
+    let signer_ata = get_associated_token_address_with_program_id(
+        &signer.pubkey(),
+        &mint_of_token_sent,
+        &token_program,
+    );
 
     let method = get_function_hash("global", "add_liquidity");
 
-    let add_liquidity = bridge_program_instructions::AddLiquidity { amount };
+    let add_liquidity = bridge_program_instructions::AddLiquidity {
+        amount: amount.amount,
+    };
 
     let mut method_bytes = method.to_vec();
 
     method_bytes.append(&mut add_liquidity.try_to_vec()?);
 
-    let ix = Instruction::new_with_bytes(
+    let mut add_liquidity_ixs = vec![Instruction::new_with_bytes(
         program_id,
         &method_bytes,
         vec![
@@ -48,30 +75,25 @@ pub async fn process_add_liquidity(
             AccountMeta::new_readonly(mint_of_token_sent, false),
             AccountMeta::new(signer_ata, false),
             AccountMeta::new_readonly(system_program::ID, false),
-            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(token_program, false),
             AccountMeta::new_readonly(spl_associated_token_account::ID, false),
         ],
-    );
-
-    let mut tx = Transaction::new_unsigned(Message::new(&[ix], Some(&signer.pubkey())));
-
-    let blockhash = rpc_client
-        .get_latest_blockhash()
-        .await
-        .map_err(|err| format!("error: unable to get latest blockhash: {err}"))?;
-
-    tx.try_sign(&vec![signer], blockhash)
-        .map_err(|err| format!("error: failed to sign transaction: {err}"))?;
-
-    let config = RpcSendTransactionConfig {
-        skip_preflight: true,
-        ..RpcSendTransactionConfig::default()
-    };
-
-    let signature = rpc_client
-        .send_transaction_with_config(&tx, config)
-        .await
-        .map_err(|err| format!("error: send transaction: {err}"))?;
-
-    Ok(signature)
+    )];
+    annotation.apply(&mut add_liquidity_ixs, &signer.pubkey());
+
+    execute_transaction(
+        rpc_client,
+        tx_config,
+        &add_liquidity_ixs,
+        fee_payer,
+        &[signer],
+    )
+    .await
+    .map(|report| {
+        report
+            .with_account("bridgeState", bridge_pda)
+            .with_account("vault", vault_ata)
+            .with_account("sourceTokenAccount", signer_ata)
+            .with_amount(amount)
+    })
 }
@@ -0,0 +1,176 @@
+use std::fmt;
+
+use serde::Serialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use spl_token::amount_to_ui_amount_string_trimmed;
+use spl_token_2022::extension::StateWithExtensions;
+use spl_token_2022::state::Mint;
+
+/// Token amount as given on the command line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Amount {
+    /// Decimal amount in whole tokens, like `1500.25`.
+    Ui(String),
+    /// Amount in base units, given with `--raw`.
+    Raw(u64),
+}
+
+impl Amount {
+    /// Converts the amount to base units for a mint with `decimals`.
+    pub fn to_token_amount(&self, decimals: u8) -> Result<TokenAmount, String> {
+        let amount = match self {
+            Amount::Ui(ui_amount) => ui_amount_to_base_units(ui_amount, decimals)?,
+            Amount::Raw(amount) => *amount,
+        };
+        Ok(TokenAmount { amount, decimals })
+    }
+}
+
+/// Parses a decimal amount into base units, rejecting more fractional digits than the mint
+/// has and amounts that do not fit in a `u64`.
+pub fn ui_amount_to_base_units(ui_amount: &str, decimals: u8) -> Result<u64, String> {
+    let (whole, fraction) = ui_amount.split_once('.').unwrap_or((ui_amount, ""));
+    if (whole.is_empty() && fraction.is_empty())
+        || !whole.bytes().all(|b| b.is_ascii_digit())
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        return Err(format!("invalid amount `{ui_amount}`"));
+    }
+
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.len() > decimals as usize {
+        return Err(format!(
+            "amount `{ui_amount}` has more than {decimals} decimal places"
+        ));
+    }
+
+    let digits = format!("{whole}{fraction:0<width$}", width = decimals as usize);
+    let digits = digits.trim_start_matches('0');
+    if digits.is_empty() {
+        return Ok(0);
+    }
+    digits
+        .parse::<u64>()
+        .map_err(|_| format!("amount `{ui_amount}` is too large"))
+}
+
+/// Amount in base units along with the mint decimals, shown in both forms.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenAmount {
+    pub amount: u64,
+    pub decimals: u8,
+}
+
+impl TokenAmount {
+    pub fn ui_amount(&self) -> String {
+        amount_to_ui_amount_string_trimmed(self.amount, self.decimals)
+    }
+}
+
+impl fmt::Display for TokenAmount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({} base units)", self.ui_amount(), self.amount)
+    }
+}
+
+impl Serialize for TokenAmount {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Amounts {
+            amount: String,
+            ui_amount: String,
+            decimals: u8,
+        }
+
+        // Base units are a string, as JSON numbers lose precision above 2^53.
+        Amounts {
+            amount: self.amount.to_string(),
+            ui_amount: self.ui_amount(),
+            decimals: self.decimals,
+        }
+        .serialize(serializer)
+    }
+}
+
+/// Returns the decimals of a mint owned by either token program.
+pub async fn get_mint_decimals(
+    rpc_client: &RpcClient,
+    mint: &Pubkey,
+) -> Result<u8, Box<dyn std::error::Error>> {
+    let data = rpc_client
+        .get_account_data(mint)
+        .await
+        .map_err(|err| format!("error: unable to get mint {mint}: {err}"))?;
+
+    let mint_state = StateWithExtensions::<Mint>::unpack(&data)
+        .map_err(|err| format!("error: invalid mint {mint}: {err}"))?;
+    Ok(mint_state.base.decimals)
+}
+
+/// Returns `mint_decimals` when given, otherwise the decimals of `mint`.
+///
+/// Passing the decimals skips the lookup, which `sign_only` requires so transactions can be
+/// built without RPC access.
+pub async fn resolve_mint_decimals(
+    rpc_client: &RpcClient,
+    mint_decimals: Option<u8>,
+    sign_only: bool,
+    mint: &Pubkey,
+) -> Result<u8, Box<dyn std::error::Error>> {
+    match mint_decimals {
+        Some(decimals) => Ok(decimals),
+        None if sign_only => Err("error: --sign-only requires --mint-decimals".into()),
+        None => get_mint_decimals(rpc_client, mint).await,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_ui_amounts_exactly() {
+        let cases = [
+            ("1500.25", 2, 150_025),
+            ("1.", 2, 100),
+            (".5", 2, 50),
+            ("007.5", 1, 75),
+            ("1.500", 1, 15),
+            ("2.000", 0, 2),
+            ("0.000", 2, 0),
+            ("0.000000001", 9, 1),
+            ("18446744073709551615", 0, u64::MAX),
+        ];
+        for (ui_amount, decimals, expected) in cases {
+            assert_eq!(
+                ui_amount_to_base_units(ui_amount, decimals),
+                Ok(expected),
+                "`{ui_amount}` with {decimals} decimals"
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_imprecise_and_oversized_amounts() {
+        let cases = [
+            ("", 9),
+            (".", 9),
+            ("1.2.3", 9),
+            ("-1", 9),
+            (" 1", 9),
+            ("1e9", 9),
+            ("0.123", 2),
+            ("1.5", 0),
+            ("18446744073709551616", 0),
+            ("18446744074", 9),
+        ];
+        for (ui_amount, decimals) in cases {
+            assert!(
+                ui_amount_to_base_units(ui_amount, decimals).is_err(),
+                "`{ui_amount}` with {decimals} decimals should be rejected"
+            );
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use std::fmt;
+
+use serde::Serialize;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+use crate::amount::{Amount, TokenAmount};
+use crate::output::Report;
+use crate::token_account::{check_source_account, get_mint_supply};
+use crate::token_program::{resolve_token_program, TokenProgram};
+use crate::transaction::{
+    execute_transaction, TransactionConfig, TransactionOutcome, TransactionReport,
+};
+
+/// Result of a burn, with the mint supply around it for reconciliation.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BurnReport {
+    #[serde(flatten)]
+    pub report: TransactionReport,
+    pub supply_before: TokenAmount,
+    /// Supply once the burn landed, absent when nothing was sent.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub supply_after: Option<TokenAmount>,
+}
+
+impl Report for BurnReport {
+    fn is_success(&self) -> bool {
+        self.report.is_success()
+    }
+}
+
+impl fmt::Display for BurnReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.report)?;
+        write!(f, "\nSupply before: {}", self.supply_before)?;
+        if let Some(supply_after) = &self.supply_after {
+            write!(f, "\nSupply after: {supply_after}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Burns from the associated token account of `signer`, or from `source` when the signer is
+/// its delegate or the permanent delegate of the mint.
+#[allow(clippy::too_many_arguments)]
+pub async fn process_burn(
+    rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
+    signer: &dyn Signer,
+    fee_payer: &dyn Signer,
+    mint_pubkey: Pubkey,
+    source: Option<Pubkey>,
+    amount: &Amount,
+    token_program: Option<TokenProgram>,
+) -> Result<BurnReport, Box<dyn std::error::Error>> {
+    let token_program = resolve_token_program(rpc_client, token_program, &mint_pubkey).await?;
+    let (supply_before, permanent_delegate) = get_mint_supply(rpc_client, &mint_pubkey).await?;
+    let amount = amount
+        .to_token_amount(supply_before.decimals)
+        .map_err(|err| format!("error: {err}"))?;
+    let source = source.unwrap_or_else(|| {
+        get_associated_token_address_with_program_id(&signer.pubkey(), &mint_pubkey, &token_program)
+    });
+
+    check_source_account(
+        rpc_client,
+        &source,
+        &mint_pubkey,
+        &signer.pubkey(),
+        permanent_delegate,
+        &amount,
+    )
+    .await?;
+
+    let burn_ix = spl_token_2022::instruction::burn_checked(
+        &token_program,
+        &source,
+        &mint_pubkey,
+        &signer.pubkey(),
+        &[],
+        amount.amount,
+        amount.decimals,
+    )?;
+
+    let report = execute_transaction(rpc_client, tx_config, &[burn_ix], fee_payer, &[signer])
+        .await?
+        .with_account("mint", mint_pubkey)
+        .with_account("tokenAccount", source)
+        .with_amount(amount);
+
+    let supply_after = match report.outcome {
+        TransactionOutcome::Landed => Some(get_mint_supply(rpc_client, &mint_pubkey).await?.0),
+        _ => None,
+    };
+
+    Ok(BurnReport {
+        report,
+        supply_before,
+        supply_after,
+    })
+}
@@ -0,0 +1,189 @@
+use std::fmt;
+
+use serde::Serialize;
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::native_token::lamports_to_sol;
+use solana_sdk::program_option::COption;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+use spl_token_2022::extension::transfer_fee::TransferFeeAmount;
+use spl_token_2022::extension::{BaseStateWithExtensions, StateWithExtensions};
+use spl_token_2022::state::Account;
+
+use crate::output::{display, Report};
+use crate::transaction::{execute_transaction, TransactionConfig, TransactionReport};
+
+/// Accounts closed per transaction, which keeps it under the size limit.
+const MAX_CLOSES_PER_TRANSACTION: usize = 20;
+
+/// Offset of the owner in the token account layout, after the mint.
+const ACCOUNT_OWNER_OFFSET: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TokenAccountStatus {
+    /// Holds no tokens and can be closed.
+    Empty,
+    /// Still holds tokens.
+    Balance,
+    /// Holds no tokens, but is frozen and has to be thawed before it can be closed.
+    Frozen,
+    /// Holds no tokens, but transfer fees are withheld in it until harvested.
+    WithheldFees,
+    /// Holds no tokens, but only its close authority can close it.
+    CloseAuthority,
+}
+
+impl fmt::Display for TokenAccountStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TokenAccountStatus::Empty => write!(f, "empty"),
+            TokenAccountStatus::Balance => write!(f, "holds tokens"),
+            TokenAccountStatus::Frozen => write!(f, "empty, frozen"),
+            TokenAccountStatus::WithheldFees => write!(f, "empty, fees withheld"),
+            TokenAccountStatus::CloseAuthority => write!(f, "empty, other close authority"),
+        }
+    }
+}
+
+/// Token account owned by the signer, under either token program.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OwnedTokenAccount {
+    #[serde(serialize_with = "display")]
+    pub pubkey: Pubkey,
+    #[serde(serialize_with = "display")]
+    pub token_program: Pubkey,
+    #[serde(serialize_with = "display")]
+    pub mint: Pubkey,
+    /// Balance in base units.
+    pub amount: u64,
+    /// Rent returned when the account is closed.
+    pub lamports: u64,
+    pub status: TokenAccountStatus,
+}
+
+impl OwnedTokenAccount {
+    pub fn is_closable(&self) -> bool {
+        self.status == TokenAccountStatus::Empty
+    }
+}
+
+impl fmt::Display for OwnedTokenAccount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Token account: {}", self.pubkey)?;
+        writeln!(f, "Mint: {}", self.mint)?;
+        writeln!(f, "Balance: {} base units", self.amount)?;
+        writeln!(f, "Rent: {} SOL", lamports_to_sol(self.lamports))?;
+        write!(f, "Status: {}", self.status)
+    }
+}
+
+/// Returns every token account owned by `owner` under both token programs.
+pub async fn get_owned_token_accounts(
+    rpc_client: &RpcClient,
+    owner: &Pubkey,
+) -> Result<Vec<OwnedTokenAccount>, Box<dyn std::error::Error>> {
+    let mut owned = Vec::new();
+    for token_program in [spl_token::ID, spl_token_2022::ID] {
+        let accounts = rpc_client
+            .get_program_accounts_with_config(
+                &token_program,
+                RpcProgramAccountsConfig {
+                    filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                        ACCOUNT_OWNER_OFFSET,
+                        owner.as_ref(),
+                    ))]),
+                    account_config: RpcAccountInfoConfig {
+                        encoding: Some(UiAccountEncoding::Base64),
+                        ..RpcAccountInfoConfig::default()
+                    },
+                    ..RpcProgramAccountsConfig::default()
+                },
+            )
+            .await
+            .map_err(|err| format!("error: unable to get token accounts of {owner}: {err}"))?;
+
+        // Mints can match the owner filter too; they do not unpack as accounts.
+        for (pubkey, account) in accounts {
+            let Ok(state) = StateWithExtensions::<Account>::unpack(&account.data) else {
+                continue;
+            };
+            if state.base.owner != *owner {
+                continue;
+            }
+
+            let other_close_authority = match state.base.close_authority {
+                COption::Some(authority) => authority != *owner,
+                COption::None => false,
+            };
+            let withheld_fees = state
+                .get_extension::<TransferFeeAmount>()
+                .is_ok_and(|fee| u64::from(fee.withheld_amount) > 0);
+            let status = if state.base.amount > 0 {
+                TokenAccountStatus::Balance
+            } else if state.base.is_frozen() {
+                TokenAccountStatus::Frozen
+            } else if withheld_fees {
+                TokenAccountStatus::WithheldFees
+            } else if other_close_authority {
+                TokenAccountStatus::CloseAuthority
+            } else {
+                TokenAccountStatus::Empty
+            };
+
+            owned.push(OwnedTokenAccount {
+                pubkey,
+                token_program,
+                mint: state.base.mint,
+                amount: state.base.amount,
+                lamports: account.lamports,
+                status,
+            });
+        }
+    }
+    Ok(owned)
+}
+
+/// Closes `accounts` in batches, sending their rent to `destination`.
+pub async fn process_close_accounts(
+    rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
+    signer: &dyn Signer,
+    fee_payer: &dyn Signer,
+    accounts: &[OwnedTokenAccount],
+    destination: Pubkey,
+) -> Result<Vec<TransactionReport>, Box<dyn std::error::Error>> {
+    if tx_config.sign_only || tx_config.nonce.is_some() {
+        return Err("error: closeAccounts does not support --sign-only or --nonce".into());
+    }
+
+    let mut reports = Vec::new();
+    for batch in accounts.chunks(MAX_CLOSES_PER_TRANSACTION) {
+        let close_ixs = batch
+            .iter()
+            .map(|account| {
+                spl_token_2022::instruction::close_account(
+                    &account.token_program,
+                    &account.pubkey,
+                    &destination,
+                    &signer.pubkey(),
+                    &[],
+                )
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let report = execute_transaction(rpc_client, tx_config, &close_ixs, fee_payer, &[signer])
+            .await?
+            .with_account("destination", destination);
+        let is_success = report.is_success();
+        reports.push(report);
+        if !is_success {
+            break;
+        }
+    }
+    Ok(reports)
+}
@@ -2,62 +2,133 @@ use mpl_token_metadata::accounts::Metadata;
 use mpl_token_metadata::instructions::CreateV1Builder;
 
 use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_client::rpc_config::RpcSendTransactionConfig;
-use solana_sdk::message::Message;
-use solana_sdk::signature::Keypair;
-use solana_sdk::system_program;
-use solana_sdk::transaction::Transaction;
-use solana_sdk::{signature::Signature, signer::Signer};
+use solana_sdk::signer::Signer;
+use solana_sdk::{system_instruction, system_program};
+use spl_token_2022::extension::ExtensionType;
+use spl_token_2022::state::Mint;
+use spl_token_metadata_interface::state::TokenMetadata;
 
+use crate::extensions::MintExtensions;
+use crate::token_program::TokenProgram;
+use crate::transaction::{execute_transaction, TransactionConfig, TransactionReport};
+
+#[allow(clippy::too_many_arguments)]
 pub async fn process_create_token(
     rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
     signer: &dyn Signer,
-    mint: Keypair,
+    fee_payer: &dyn Signer,
+    mint: &dyn Signer,
     decimals: u8,
     name: String,
     symbol: String,
     uri: String,
-) -> Result<Signature, Box<dyn std::error::Error>> {
-    let (metadata, _) = Metadata::find_pda(&mint.pubkey());
-
-    let create_ix = CreateV1Builder::new()
-        .metadata(metadata)
-        .mint(mint.pubkey(), true)
-        .authority(signer.pubkey())
-        .payer(signer.pubkey())
-        .update_authority(signer.pubkey(), true)
-        .is_mutable(true)
-        .primary_sale_happened(false)
-        .name(name)
-        .uri(uri)
-        .symbol(symbol)
-        .seller_fee_basis_points(0)
-        .token_standard(mpl_token_metadata::types::TokenStandard::Fungible)
-        .decimals(decimals)
-        .spl_token_program(Some(spl_token::ID))
-        .system_program(system_program::ID)
-        .sysvar_instructions(solana_program::sysvar::instructions::ID)
-        .instruction();
-
-    let mut tx = Transaction::new_unsigned(Message::new(&[create_ix], Some(&signer.pubkey())));
-
-    let blockhash = rpc_client
-        .get_latest_blockhash()
-        .await
-        .map_err(|err| format!("error: unable to get latest blockhash: {err}"))?;
-
-    tx.try_sign(&vec![signer, &mint], blockhash)
-        .map_err(|err| format!("error: failed to sign transaction: {err}"))?;
-
-    let config = RpcSendTransactionConfig {
-        skip_preflight: true,
-        ..RpcSendTransactionConfig::default()
-    };
+    token_program: TokenProgram,
+    extensions: &MintExtensions,
+) -> Result<TransactionReport, Box<dyn std::error::Error>> {
+    let mut instructions = Vec::new();
+
+    // Token Metadata can only create plain mints, so a mint with extensions is created and
+    // initialized here first, and then handed to it as an existing mint.
+    if !extensions.is_empty() {
+        if token_program != TokenProgram::Token2022 {
+            return Err("error: mint extensions require --token-program 2022".into());
+        }
+        let token_program = token_program.id();
+
+        let space = ExtensionType::try_calculate_account_len::<Mint>(&extensions.extension_types())
+            .map_err(|err| format!("error: unable to size mint account: {err}"))?;
+
+        // The token metadata initialize instruction reallocates the mint to fit the metadata,
+        // so the account has to be funded for that size upfront.
+        let mut rent_space = space;
+        if extensions.metadata_pointer {
+            let token_metadata = TokenMetadata {
+                name: name.clone(),
+                symbol: symbol.clone(),
+                uri: uri.clone(),
+                ..Default::default()
+            };
+            rent_space += token_metadata
+                .tlv_size_of()
+                .map_err(|err| format!("error: unable to size token metadata: {err}"))?;
+        }
 
-    let signature = rpc_client
-        .send_transaction_with_config(&tx, config)
-        .await
-        .map_err(|err| format!("error: send transaction: {err}"))?;
+        let lamports = rpc_client
+            .get_minimum_balance_for_rent_exemption(rent_space)
+            .await
+            .map_err(|err| format!("error: unable to get rent exemption: {err}"))?;
+
+        instructions.push(system_instruction::create_account(
+            &fee_payer.pubkey(),
+            &mint.pubkey(),
+            lamports,
+            space as u64,
+            &token_program,
+        ));
+        instructions.extend(extensions.instructions(
+            &token_program,
+            &mint.pubkey(),
+            &signer.pubkey(),
+        )?);
+        instructions.push(spl_token_2022::instruction::initialize_mint2(
+            &token_program,
+            &mint.pubkey(),
+            &signer.pubkey(),
+            Some(&signer.pubkey()),
+            decimals,
+        )?);
+    }
+
+    let metadata = if extensions.metadata_pointer {
+        instructions.push(spl_token_metadata_interface::instruction::initialize(
+            &token_program.id(),
+            &mint.pubkey(),
+            &signer.pubkey(),
+            &mint.pubkey(),
+            &signer.pubkey(),
+            name,
+            symbol,
+            uri,
+        ));
+        mint.pubkey()
+    } else {
+        let (metadata, _) = Metadata::find_pda(&mint.pubkey());
+
+        instructions.push(
+            CreateV1Builder::new()
+                .metadata(metadata)
+                .mint(mint.pubkey(), true)
+                .authority(signer.pubkey())
+                .payer(fee_payer.pubkey())
+                .update_authority(signer.pubkey(), true)
+                .is_mutable(true)
+                .primary_sale_happened(false)
+                .name(name)
+                .uri(uri)
+                .symbol(symbol)
+                .seller_fee_basis_points(0)
+                .token_standard(mpl_token_metadata::types::TokenStandard::Fungible)
+                .decimals(decimals)
+                .spl_token_program(Some(token_program.id()))
+                .system_program(system_program::ID)
+                .sysvar_instructions(solana_program::sysvar::instructions::ID)
+                .instruction(),
+        );
+        metadata
+    };
 
-    Ok(signature)
+    execute_transaction(
+        rpc_client,
+        tx_config,
+        &instructions,
+        fee_payer,
+        &[signer, mint],
+    )
+    .await
+    .map(|report| {
+        report
+            .with_account("mint", mint.pubkey())
+            .with_account("metadata", metadata)
+    })
 }
@@ -0,0 +1,84 @@
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+use crate::amount::{resolve_mint_decimals, Amount};
+use crate::token_program::{resolve_token_program, TokenProgram};
+use crate::transaction::{execute_transaction, TransactionConfig, TransactionReport};
+
+/// Allows `delegate` to spend up to `amount` from the associated token account of `signer`,
+/// replacing any earlier allowance.
+#[allow(clippy::too_many_arguments)]
+pub async fn process_approve(
+    rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
+    signer: &dyn Signer,
+    fee_payer: &dyn Signer,
+    mint_pubkey: Pubkey,
+    delegate: Pubkey,
+    amount: &Amount,
+    token_program: Option<TokenProgram>,
+    mint_decimals: Option<u8>,
+) -> Result<TransactionReport, Box<dyn std::error::Error>> {
+    let token_program = resolve_token_program(rpc_client, token_program, &mint_pubkey).await?;
+    let decimals =
+        resolve_mint_decimals(rpc_client, mint_decimals, tx_config.sign_only, &mint_pubkey).await?;
+    let amount = amount
+        .to_token_amount(decimals)
+        .map_err(|err| format!("error: {err}"))?;
+    let signer_ata = get_associated_token_address_with_program_id(
+        &signer.pubkey(),
+        &mint_pubkey,
+        &token_program,
+    );
+
+    let approve_ix = spl_token_2022::instruction::approve_checked(
+        &token_program,
+        &signer_ata,
+        &mint_pubkey,
+        &delegate,
+        &signer.pubkey(),
+        &[],
+        amount.amount,
+        amount.decimals,
+    )?;
+
+    execute_transaction(rpc_client, tx_config, &[approve_ix], fee_payer, &[signer])
+        .await
+        .map(|report| {
+            report
+                .with_account("mint", mint_pubkey)
+                .with_account("tokenAccount", signer_ata)
+                .with_account("delegate", delegate)
+                .with_amount(amount)
+        })
+}
+
+/// Removes the allowance on the associated token account of `signer`.
+pub async fn process_revoke(
+    rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
+    signer: &dyn Signer,
+    fee_payer: &dyn Signer,
+    mint_pubkey: Pubkey,
+    token_program: Option<TokenProgram>,
+) -> Result<TransactionReport, Box<dyn std::error::Error>> {
+    let token_program = resolve_token_program(rpc_client, token_program, &mint_pubkey).await?;
+    let signer_ata = get_associated_token_address_with_program_id(
+        &signer.pubkey(),
+        &mint_pubkey,
+        &token_program,
+    );
+
+    let revoke_ix =
+        spl_token_2022::instruction::revoke(&token_program, &signer_ata, &signer.pubkey(), &[])?;
+
+    execute_transaction(rpc_client, tx_config, &[revoke_ix], fee_payer, &[signer])
+        .await
+        .map(|report| {
+            report
+                .with_account("mint", mint_pubkey)
+                .with_account("tokenAccount", signer_ata)
+        })
+}
@@ -0,0 +1,735 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use futures_util::stream::{self, StreamExt};
+use mpl_token_metadata::accounts::Metadata;
+use serde::{Deserialize, Serialize};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::hash::Hash;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::Signer;
+
+use crate::amount::{get_mint_decimals, Amount, TokenAmount};
+use crate::mint_to::{has_metadata_account, mint_to_instructions};
+use crate::output::Report;
+use crate::token_program::{resolve_token_program, TokenProgram};
+use crate::transaction::{
+    execute_transaction_with_hook, TransactionConfig, TransactionOutcome, TransactionReport,
+};
+use crate::transfer_to::{
+    get_programmable_metadata, programmable_transfer_instruction, transfer_checked_instructions,
+};
+
+/// Most signatures `getSignatureStatuses` accepts per request.
+const MAX_SIGNATURE_STATUSES: usize = 256;
+
+/// How `distribute` hands out tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DistributionMode {
+    /// Transfer from the signer's associated token account.
+    Transfer,
+    /// Mint new tokens with the signer as mint authority.
+    Mint,
+}
+
+pub struct DistributionConfig {
+    pub csv_path: String,
+    pub state_path: String,
+    pub mode: DistributionMode,
+    /// Amounts in the CSV are in base units.
+    pub raw: bool,
+    pub batch_size: usize,
+    pub concurrency: usize,
+}
+
+/// A row of the distribution CSV.
+#[derive(Debug, Clone)]
+struct Recipient {
+    /// Line number in the CSV, which identifies the row in the state file.
+    line: usize,
+    recipient: Pubkey,
+    amount: TokenAmount,
+    memo: Option<String>,
+}
+
+fn read_recipients(
+    path: &str,
+    decimals: u8,
+    raw: bool,
+) -> Result<Vec<Recipient>, Box<dyn std::error::Error>> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|err| format!("error: unable to read {path}: {err}"))?;
+    parse_recipients(&contents, path, decimals, raw)
+}
+
+/// Parses `recipient,amount[,memo]` rows. Empty lines, `#` comments and a header row are
+/// skipped, and the memo is the rest of the line so it may contain commas.
+fn parse_recipients(
+    contents: &str,
+    path: &str,
+    decimals: u8,
+    raw: bool,
+) -> Result<Vec<Recipient>, Box<dyn std::error::Error>> {
+    let mut recipients = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        let line_number = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut fields = line.splitn(3, ',').map(str::trim);
+        let recipient = fields.next().unwrap_or_default();
+        if recipients.is_empty() && recipient.eq_ignore_ascii_case("recipient") {
+            continue;
+        }
+        let amount = fields
+            .next()
+            .ok_or_else(|| format!("error: {path}:{line_number}: missing amount"))?;
+        let memo = fields.next().filter(|memo| !memo.is_empty());
+
+        let recipient = Pubkey::from_str(recipient).map_err(|err| {
+            format!("error: {path}:{line_number}: invalid recipient `{recipient}`: {err}")
+        })?;
+        let amount = if raw {
+            amount
+                .parse()
+                .map(Amount::Raw)
+                .map_err(|_| format!("invalid base unit amount `{amount}`"))
+        } else {
+            Ok(Amount::Ui(amount.to_string()))
+        }
+        .and_then(|amount| amount.to_token_amount(decimals))
+        .map_err(|err| format!("error: {path}:{line_number}: {err}"))?;
+
+        recipients.push(Recipient {
+            line: line_number,
+            recipient,
+            amount,
+            memo: memo.map(str::to_string),
+        });
+    }
+    Ok(recipients)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+enum RowStatus {
+    /// Signed and about to be sent, it may or may not have landed.
+    Pending,
+    Landed,
+    Failed,
+    Expired,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RowState {
+    recipient: String,
+    /// Amount in base units.
+    amount: String,
+    status: RowStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    signature: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    blockhash: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Progress of a distribution, keyed by CSV line number.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DistributionState {
+    mint: String,
+    mode: DistributionMode,
+    rows: BTreeMap<usize, RowState>,
+}
+
+impl DistributionState {
+    fn load(
+        path: &str,
+        mint: &Pubkey,
+        mode: DistributionMode,
+        recipients: &[Recipient],
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        if !Path::new(path).exists() {
+            return Ok(DistributionState {
+                mint: mint.to_string(),
+                mode,
+                rows: BTreeMap::new(),
+            });
+        }
+
+        let contents = std::fs::read_to_string(path)
+            .map_err(|err| format!("error: unable to read state file {path}: {err}"))?;
+        let state: DistributionState = serde_json::from_str(&contents)
+            .map_err(|err| format!("error: invalid state file {path}: {err}"))?;
+
+        if state.mint != mint.to_string() || state.mode != mode {
+            return Err(format!(
+                "error: state file {path} belongs to a {:?} distribution of {}",
+                state.mode, state.mint
+            )
+            .into());
+        }
+        // Rows are matched by line number, so an edited CSV could pay the wrong recipient.
+        for recipient in recipients {
+            if let Some(row) = state.rows.get(&recipient.line) {
+                if row.recipient != recipient.recipient.to_string()
+                    || row.amount != recipient.amount.amount.to_string()
+                {
+                    return Err(format!(
+                        "error: line {} of the CSV changed since state file {path} was written",
+                        recipient.line
+                    )
+                    .into());
+                }
+            }
+        }
+        Ok(state)
+    }
+
+    /// Writes the state through a temporary file, so a crash never leaves it truncated.
+    fn save(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let tmp_path = format!("{path}.tmp");
+        std::fs::write(&tmp_path, serde_json::to_string_pretty(self)?)
+            .map_err(|err| format!("error: unable to write state file {tmp_path}: {err}"))?;
+        std::fs::rename(&tmp_path, path)
+            .map_err(|err| format!("error: unable to write state file {path}: {err}"))?;
+        Ok(())
+    }
+
+    fn set_row(&mut self, recipient: &Recipient, status: RowStatus) -> &mut RowState {
+        let row = self.rows.entry(recipient.line).or_insert_with(|| RowState {
+            recipient: recipient.recipient.to_string(),
+            amount: recipient.amount.amount.to_string(),
+            status,
+            signature: None,
+            blockhash: None,
+            error: None,
+        });
+        row.status = status;
+        row
+    }
+
+    fn is_landed(&self, recipient: &Recipient) -> bool {
+        self.rows
+            .get(&recipient.line)
+            .is_some_and(|row| row.status == RowStatus::Landed)
+    }
+
+    /// Returns the signatures and blockhashes of pending rows. Pending rows without a
+    /// signature were never sent, so they are marked failed.
+    fn pending_signatures(&mut self) -> HashMap<Signature, Option<Hash>> {
+        let mut pending = HashMap::new();
+        for row in self.rows.values_mut() {
+            if row.status != RowStatus::Pending {
+                continue;
+            }
+            match row.signature.as_deref().map(Signature::from_str) {
+                Some(Ok(signature)) => {
+                    let blockhash = row.blockhash.as_deref().and_then(|b| b.parse().ok());
+                    pending.insert(signature, blockhash);
+                }
+                // Never signed, so it was never sent.
+                _ => row.status = RowStatus::Failed,
+            }
+        }
+        pending
+    }
+
+    /// Sets pending rows to the status found for their signature in `outcomes`.
+    fn apply_outcomes(&mut self, outcomes: &HashMap<String, (RowStatus, Option<String>)>) {
+        for row in self.rows.values_mut() {
+            if let Some((status, error)) = row.signature.as_ref().and_then(|s| outcomes.get(s)) {
+                if row.status == RowStatus::Pending {
+                    row.status = *status;
+                    row.error = error.clone();
+                }
+            }
+        }
+    }
+
+    /// Resolves rows left pending by an interrupted run. Their transaction either landed, or
+    /// can no longer land once its blockhash has expired, and only then is it safe to resend.
+    async fn resolve_pending(
+        &mut self,
+        rpc_client: &RpcClient,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let pending = self.pending_signatures();
+
+        let signatures: Vec<Signature> = pending.keys().copied().collect();
+        let mut outcomes = HashMap::new();
+        for chunk in signatures.chunks(MAX_SIGNATURE_STATUSES) {
+            let statuses = rpc_client
+                .get_signature_statuses_with_history(chunk)
+                .await
+                .map_err(|err| format!("error: unable to get signature statuses: {err}"))?
+                .value;
+
+            for (signature, status) in chunk.iter().zip(statuses) {
+                let outcome = match status {
+                    Some(status) if status.err.is_some() => {
+                        (RowStatus::Failed, status.err.map(|err| err.to_string()))
+                    }
+                    Some(status) if status.satisfies_commitment(rpc_client.commitment()) => {
+                        (RowStatus::Landed, None)
+                    }
+                    Some(_) => {
+                        return Err(format!(
+                            "error: transaction {signature} from a previous run is still \
+                             confirming, try again shortly"
+                        )
+                        .into())
+                    }
+                    None => {
+                        let blockhash = pending[signature];
+                        let still_valid = match blockhash {
+                            Some(blockhash) => rpc_client
+                                .is_blockhash_valid(&blockhash, rpc_client.commitment())
+                                .await
+                                .map_err(|err| {
+                                    format!("error: unable to check blockhash {blockhash}: {err}")
+                                })?,
+                            None => false,
+                        };
+                        if still_valid {
+                            return Err(format!(
+                                "error: transaction {signature} from a previous run may still \
+                                 land, try again once its blockhash expires"
+                            )
+                            .into());
+                        }
+                        (RowStatus::Expired, None)
+                    }
+                };
+                outcomes.insert(signature.to_string(), outcome);
+            }
+        }
+
+        self.apply_outcomes(&outcomes);
+        Ok(())
+    }
+
+    /// Records the result of sending `batch`.
+    fn record_result(
+        &mut self,
+        batch: &[Recipient],
+        result: &Result<TransactionReport, Box<dyn std::error::Error>>,
+    ) {
+        for recipient in batch {
+            let sent = self
+                .rows
+                .get(&recipient.line)
+                .is_some_and(|row| row.status == RowStatus::Pending);
+            let status = match result {
+                Ok(report) => match report.outcome {
+                    TransactionOutcome::Landed => RowStatus::Landed,
+                    TransactionOutcome::Failed => RowStatus::Failed,
+                    TransactionOutcome::Expired => RowStatus::Expired,
+                    // It may still land, leave it to the next run to find out.
+                    TransactionOutcome::Unconfirmed => continue,
+                    TransactionOutcome::DryRun | TransactionOutcome::SignOnly => continue,
+                },
+                // It may have been sent before the error, so leave it to the next run to
+                // find out whether it landed.
+                Err(_) if sent => continue,
+                Err(_) => RowStatus::Failed,
+            };
+            let row = self.set_row(recipient, status);
+            row.error = match result {
+                Ok(report) => report.error.as_ref().map(ToString::to_string),
+                Err(err) => Some(err.to_string()),
+            };
+        }
+    }
+}
+
+/// Result of one distribution transaction.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchReport {
+    /// CSV line numbers of the rows in the transaction.
+    pub lines: Vec<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub report: Option<TransactionReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl BatchReport {
+    fn is_success(&self) -> bool {
+        self.report
+            .as_ref()
+            .is_some_and(TransactionReport::is_success)
+    }
+}
+
+impl fmt::Display for BatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lines: Vec<String> = self.lines.iter().map(ToString::to_string).collect();
+        writeln!(f, "Lines: {}", lines.join(", "))?;
+        if let Some(report) = &self.report {
+            write!(f, "{report}")?;
+        }
+        if let Some(error) = &self.error {
+            write!(f, "Error: {error}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DistributionReport {
+    pub rows: usize,
+    /// Rows that have landed, in this run or an earlier one.
+    pub landed: usize,
+    pub batches: Vec<BatchReport>,
+}
+
+impl Report for DistributionReport {
+    fn is_success(&self) -> bool {
+        self.batches.iter().all(BatchReport::is_success)
+    }
+}
+
+impl fmt::Display for DistributionReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for batch in &self.batches {
+            writeln!(f, "{batch}\n")?;
+        }
+        write!(f, "Landed {} of {} rows", self.landed, self.rows)
+    }
+}
+
+struct Distribution<'a> {
+    rpc_client: &'a RpcClient,
+    tx_config: &'a TransactionConfig,
+    signer: &'a dyn Signer,
+    fee_payer: &'a dyn Signer,
+    mint: Pubkey,
+    token_program: Pubkey,
+    decimals: u8,
+    has_metadata_account: bool,
+    programmable_metadata: Option<Metadata>,
+    config: &'a DistributionConfig,
+    state: RefCell<DistributionState>,
+}
+
+impl Distribution<'_> {
+    fn save_state(&self) -> Result<(), Box<dyn std::error::Error>> {
+        if self.tx_config.dry_run {
+            return Ok(());
+        }
+        self.state.borrow().save(&self.config.state_path)
+    }
+
+    async fn send_batch(&self, batch: &[Recipient]) -> BatchReport {
+        let lines = batch.iter().map(|recipient| recipient.line).collect();
+        match self.try_send_batch(batch).await {
+            Ok(report) => BatchReport {
+                lines,
+                report: Some(report),
+                error: None,
+            },
+            Err(err) => BatchReport {
+                lines,
+                report: None,
+                error: Some(err.to_string()),
+            },
+        }
+    }
+
+    async fn try_send_batch(
+        &self,
+        batch: &[Recipient],
+    ) -> Result<TransactionReport, Box<dyn std::error::Error>> {
+        let signer = self.signer.pubkey();
+        let fee_payer = self.fee_payer.pubkey();
+
+        let mut instructions = Vec::new();
+        for recipient in batch {
+            match self.config.mode {
+                DistributionMode::Transfer => match &self.programmable_metadata {
+                    Some(metadata) => instructions.push(programmable_transfer_instruction(
+                        &signer,
+                        &fee_payer,
+                        metadata,
+                        &recipient.recipient,
+                        recipient.amount.amount,
+                        &self.token_program,
+                    )),
+                    None => instructions.extend(transfer_checked_instructions(
+                        &signer,
+                        &signer,
+                        &fee_payer,
+                        &self.mint,
+                        &recipient.recipient,
+                        recipient.amount.amount,
+                        self.decimals,
+                        &self.token_program,
+                    )?),
+                },
+                DistributionMode::Mint => instructions.extend(mint_to_instructions(
+                    &signer,
+                    &fee_payer,
+                    &self.mint,
+                    &recipient.recipient,
+                    recipient.amount.amount,
+                    &self.token_program,
+                    self.has_metadata_account,
+                )?),
+            }
+            if let Some(memo) = &recipient.memo {
+                instructions.push(spl_memo::build_memo(memo.as_bytes(), &[&signer]));
+            }
+        }
+
+        // The signature is recorded before sending, so the rows are never paid twice.
+        let record_pending = |signature: &Signature, blockhash: &Hash| {
+            let mut state = self.state.borrow_mut();
+            for recipient in batch {
+                let row = state.set_row(recipient, RowStatus::Pending);
+                row.signature = Some(signature.to_string());
+                row.blockhash = Some(blockhash.to_string());
+                row.error = None;
+            }
+            drop(state);
+            self.save_state()
+        };
+
+        let result = execute_transaction_with_hook(
+            self.rpc_client,
+            self.tx_config,
+            &instructions,
+            self.fee_payer,
+            &[self.signer],
+            &record_pending,
+        )
+        .await;
+
+        self.state.borrow_mut().record_result(batch, &result);
+        self.save_state()?;
+
+        result
+    }
+}
+
+/// Sends tokens to every row of the CSV at `config.csv_path` that has not landed yet, recording
+/// progress in the state file so an interrupted or partly failed run can be resumed.
+pub async fn process_distribute(
+    rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
+    signer: &dyn Signer,
+    fee_payer: &dyn Signer,
+    mint_pubkey: Pubkey,
+    config: &DistributionConfig,
+    token_program: Option<TokenProgram>,
+) -> Result<DistributionReport, Box<dyn std::error::Error>> {
+    if tx_config.sign_only || tx_config.nonce.is_some() {
+        return Err("error: distribute does not support --sign-only or --nonce".into());
+    }
+
+    let token_program = resolve_token_program(rpc_client, token_program, &mint_pubkey).await?;
+    let decimals = get_mint_decimals(rpc_client, &mint_pubkey).await?;
+    let recipients = read_recipients(&config.csv_path, decimals, config.raw)?;
+
+    let (has_metadata_account, programmable_metadata) = match config.mode {
+        DistributionMode::Mint => (
+            has_metadata_account(rpc_client, &token_program, &mint_pubkey).await?,
+            None,
+        ),
+        DistributionMode::Transfer => (
+            true,
+            get_programmable_metadata(rpc_client, &mint_pubkey).await?,
+        ),
+    };
+
+    let mut state =
+        DistributionState::load(&config.state_path, &mint_pubkey, config.mode, &recipients)?;
+    state.resolve_pending(rpc_client).await?;
+
+    let distribution = Distribution {
+        rpc_client,
+        tx_config,
+        signer,
+        fee_payer,
+        mint: mint_pubkey,
+        token_program,
+        decimals,
+        has_metadata_account,
+        programmable_metadata,
+        config,
+        state: RefCell::new(state),
+    };
+    distribution.save_state()?;
+
+    let remaining: Vec<Recipient> = recipients
+        .iter()
+        .filter(|recipient| !distribution.state.borrow().is_landed(recipient))
+        .cloned()
+        .collect();
+
+    let mut batches = stream::iter(remaining.chunks(config.batch_size.max(1)))
+        .map(|batch| distribution.send_batch(batch))
+        .buffer_unordered(config.concurrency.max(1))
+        .collect::<Vec<_>>()
+        .await;
+    batches.sort_by_key(|batch| batch.lines.first().copied());
+
+    let state = distribution.state.borrow();
+    Ok(DistributionReport {
+        rows: recipients.len(),
+        landed: recipients
+            .iter()
+            .filter(|recipient| state.is_landed(recipient))
+            .count(),
+        batches,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn recipient(line: usize, amount: u64) -> Recipient {
+        Recipient {
+            line,
+            recipient: Pubkey::new_unique(),
+            amount: TokenAmount {
+                amount,
+                decimals: 2,
+            },
+            memo: None,
+        }
+    }
+
+    fn empty_state() -> DistributionState {
+        DistributionState {
+            mint: Pubkey::new_unique().to_string(),
+            mode: DistributionMode::Transfer,
+            rows: BTreeMap::new(),
+        }
+    }
+
+    /// A directory of its own for each test, so parallel runs never share a state file.
+    fn temp_dir(name: &str) -> std::path::PathBuf {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir =
+            std::env::temp_dir().join(format!("distribute-{name}-{}-{nanos}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn parses_rows_and_skips_header_comments_and_blank_lines() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let csv =
+            format!("# rewards\nrecipient,amount,memo\n\n{a},1.5,March, April\n  {b} , 2 ,\n");
+        let recipients = parse_recipients(&csv, "test.csv", 2, false).unwrap();
+
+        assert_eq!(recipients.len(), 2);
+        assert_eq!(recipients[0].line, 4);
+        assert_eq!(recipients[0].amount.amount, 150);
+        assert_eq!(recipients[0].memo.as_deref(), Some("March, April"));
+        assert_eq!(recipients[1].line, 5);
+        assert_eq!(recipients[1].recipient, b);
+        assert_eq!(recipients[1].memo, None);
+    }
+
+    #[test]
+    fn reports_the_line_of_bad_rows() {
+        let csv = format!("# header\n{},1.234\n", Pubkey::new_unique());
+        let err = parse_recipients(&csv, "test.csv", 2, false).unwrap_err();
+        assert!(err.to_string().contains("test.csv:2"), "{err}");
+
+        let csv = format!("{},1\nrecipient,amount\n", Pubkey::new_unique());
+        let err = parse_recipients(&csv, "test.csv", 0, false).unwrap_err();
+        assert!(err.to_string().contains("test.csv:2"), "{err}");
+    }
+
+    #[test]
+    fn resumed_state_only_skips_landed_rows() {
+        let dir = temp_dir("resume");
+        let path = dir.join("state.json").to_string_lossy().into_owned();
+        let mint = Pubkey::new_unique();
+        let recipients = [recipient(1, 100), recipient(2, 200), recipient(3, 300)];
+        let mut state =
+            DistributionState::load(&path, &mint, DistributionMode::Mint, &recipients).unwrap();
+        state.set_row(&recipients[0], RowStatus::Landed);
+        state.set_row(&recipients[1], RowStatus::Pending);
+        state.set_row(&recipients[2], RowStatus::Expired);
+        state.save(&path).unwrap();
+
+        let state =
+            DistributionState::load(&path, &mint, DistributionMode::Mint, &recipients).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(state.is_landed(&recipients[0]));
+        assert!(!state.is_landed(&recipients[1]));
+        assert!(!state.is_landed(&recipients[2]));
+    }
+
+    #[test]
+    fn pending_rows_without_a_signature_are_failed() {
+        let recipients = [recipient(1, 100), recipient(2, 200), recipient(3, 300)];
+        let mut state = empty_state();
+        let signature = Signature::new_unique();
+        let blockhash = Hash::new_unique();
+        let row = state.set_row(&recipients[0], RowStatus::Pending);
+        row.signature = Some(signature.to_string());
+        row.blockhash = Some(blockhash.to_string());
+        state.set_row(&recipients[1], RowStatus::Pending);
+        state.set_row(&recipients[2], RowStatus::Expired);
+
+        let pending = state.pending_signatures();
+
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[&signature], Some(blockhash));
+        assert_eq!(state.rows[&2].status, RowStatus::Failed);
+        assert_eq!(state.rows[&3].status, RowStatus::Expired);
+    }
+
+    #[test]
+    fn outcomes_only_change_pending_rows() {
+        let recipients = [recipient(1, 100), recipient(2, 200)];
+        let mut state = empty_state();
+        let expired = Signature::new_unique().to_string();
+        let landed = Signature::new_unique().to_string();
+        state.set_row(&recipients[0], RowStatus::Pending).signature = Some(expired.clone());
+        state.set_row(&recipients[1], RowStatus::Landed).signature = Some(landed.clone());
+
+        let outcomes = HashMap::from([
+            (expired, (RowStatus::Expired, None)),
+            (landed, (RowStatus::Expired, None)),
+        ]);
+        state.apply_outcomes(&outcomes);
+
+        assert_eq!(state.rows[&1].status, RowStatus::Expired);
+        assert_eq!(state.rows[&2].status, RowStatus::Landed);
+    }
+
+    #[test]
+    fn errors_after_sending_leave_the_rows_pending() {
+        let recipients = [recipient(1, 100), recipient(2, 200)];
+        let mut state = empty_state();
+        state.set_row(&recipients[0], RowStatus::Pending).signature =
+            Some(Signature::new_unique().to_string());
+        state.set_row(&recipients[1], RowStatus::Expired);
+
+        state.record_result(&recipients, &Err("send transaction: timed out".into()));
+
+        assert_eq!(state.rows[&1].status, RowStatus::Pending);
+        assert_eq!(state.rows[&2].status, RowStatus::Failed);
+    }
+}
@@ -0,0 +1,167 @@
+use std::str::FromStr;
+
+use serde::Deserialize;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::program_error::ProgramError;
+use solana_sdk::pubkey::Pubkey;
+use spl_token_2022::extension::{
+    default_account_state, interest_bearing_mint, metadata_pointer, transfer_fee, ExtensionType,
+};
+use spl_token_2022::instruction::{
+    initialize_mint_close_authority, initialize_non_transferable_mint,
+    initialize_permanent_delegate,
+};
+use spl_token_2022::state::AccountState;
+
+use crate::utils::from_str_option;
+
+/// Token-2022 extensions to initialize on a new mint, read from `--extensions-config` and the
+/// extension flags of `createTokenWithMetadata`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MintExtensions {
+    pub transfer_fee: Option<TransferFee>,
+    /// Stores the metadata in the mint with the token-metadata interface instead of a Token
+    /// Metadata account.
+    pub metadata_pointer: bool,
+    #[serde(deserialize_with = "from_str_option")]
+    pub permanent_delegate: Option<Pubkey>,
+    pub default_account_state: Option<DefaultAccountState>,
+    pub non_transferable: bool,
+    /// Interest rate in basis points.
+    pub interest_rate: Option<i16>,
+    #[serde(deserialize_with = "from_str_option")]
+    pub close_authority: Option<Pubkey>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TransferFee {
+    pub basis_points: u16,
+    /// Maximum fee per transfer, in base units.
+    pub maximum_fee: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DefaultAccountState {
+    Initialized,
+    Frozen,
+}
+
+impl FromStr for DefaultAccountState {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "initialized" => Ok(DefaultAccountState::Initialized),
+            "frozen" => Ok(DefaultAccountState::Frozen),
+            _ => Err(format!("expected `initialized` or `frozen`, got `{s}`")),
+        }
+    }
+}
+
+impl MintExtensions {
+    pub fn is_empty(&self) -> bool {
+        self.extension_types().is_empty()
+    }
+
+    /// Extensions stored in the mint account, used to size it.
+    ///
+    /// The token metadata itself is variable length and is added by its own initialize
+    /// instruction, so only the pointer is listed here.
+    pub fn extension_types(&self) -> Vec<ExtensionType> {
+        let mut extension_types = Vec::new();
+        if self.transfer_fee.is_some() {
+            extension_types.push(ExtensionType::TransferFeeConfig);
+        }
+        if self.metadata_pointer {
+            extension_types.push(ExtensionType::MetadataPointer);
+        }
+        if self.permanent_delegate.is_some() {
+            extension_types.push(ExtensionType::PermanentDelegate);
+        }
+        if self.default_account_state.is_some() {
+            extension_types.push(ExtensionType::DefaultAccountState);
+        }
+        if self.non_transferable {
+            extension_types.push(ExtensionType::NonTransferable);
+        }
+        if self.interest_rate.is_some() {
+            extension_types.push(ExtensionType::InterestBearingConfig);
+        }
+        if self.close_authority.is_some() {
+            extension_types.push(ExtensionType::MintCloseAuthority);
+        }
+        extension_types
+    }
+
+    /// Instructions initializing the extensions, which must run after the mint account is
+    /// created and before `initialize_mint2`. `authority` becomes the authority of every
+    /// extension that has one.
+    pub fn instructions(
+        &self,
+        token_program: &Pubkey,
+        mint: &Pubkey,
+        authority: &Pubkey,
+    ) -> Result<Vec<Instruction>, ProgramError> {
+        let mut instructions = Vec::new();
+        if let Some(fee) = self.transfer_fee {
+            instructions.push(transfer_fee::instruction::initialize_transfer_fee_config(
+                token_program,
+                mint,
+                Some(authority),
+                Some(authority),
+                fee.basis_points,
+                fee.maximum_fee,
+            )?);
+        }
+        if self.metadata_pointer {
+            instructions.push(metadata_pointer::instruction::initialize(
+                token_program,
+                mint,
+                Some(*authority),
+                Some(*mint),
+            )?);
+        }
+        if let Some(delegate) = &self.permanent_delegate {
+            instructions.push(initialize_permanent_delegate(
+                token_program,
+                mint,
+                delegate,
+            )?);
+        }
+        if let Some(state) = self.default_account_state {
+            let state = match state {
+                DefaultAccountState::Initialized => AccountState::Initialized,
+                DefaultAccountState::Frozen => AccountState::Frozen,
+            };
+            instructions.push(
+                default_account_state::instruction::initialize_default_account_state(
+                    token_program,
+                    mint,
+                    &state,
+                )?,
+            );
+        }
+        if self.non_transferable {
+            instructions.push(initialize_non_transferable_mint(token_program, mint)?);
+        }
+        if let Some(rate) = self.interest_rate {
+            instructions.push(interest_bearing_mint::instruction::initialize(
+                token_program,
+                mint,
+                Some(*authority),
+                rate,
+            )?);
+        }
+        if let Some(close_authority) = &self.close_authority {
+            instructions.push(initialize_mint_close_authority(
+                token_program,
+                mint,
+                Some(close_authority),
+            )?);
+        }
+        Ok(instructions)
+    }
+}
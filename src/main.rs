@@ -1,51 +1,268 @@
+use std::io::IsTerminal;
 use std::str::FromStr;
 
-use crate::{authorities::process_update_authorities, update_metadata::process_update_metadata};
+use crate::authorities::process_update_authorities;
+use crate::update_metadata::{
+    get_metadata, process_update_metadata, prompt_metadata_patch, CreatorPatch, MetadataPatch,
+    TokenStandardArg, UsesPatch,
+};
 
 use add_liquidity::process_add_liquidity;
+use amount::Amount;
+use burn::process_burn;
+use close_accounts::{
+    get_owned_token_accounts, process_close_accounts, OwnedTokenAccount, TokenAccountStatus,
+};
+use delegate::{process_approve, process_revoke};
+use distribute::{process_distribute, DistributionConfig, DistributionMode};
+use extensions::{DefaultAccountState, MintExtensions, TransferFee};
+use memo::Annotation;
+use metadata_diff::{data_of, print_metadata, print_metadata_diff};
+use metadata_json::{
+    check_onchain_fields, validate_metadata_file, FungibleMetadataJson, ValidationReport,
+};
+use output::{OutputFormat, Report};
 use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+use token_program::{resolve_token_program, TokenProgram};
+use transaction::{ComputeBudgetSetting, NonceConfig, TransactionConfig, TransactionReport};
+use utils::{read_config_file, write_keypair_file_new};
+use verify::{process_sign_metadata, process_verify_collection};
 
 use {
-    crate::{mint_to::process_mint_to, transfer_to::process_transfer_to},
-    clap::{crate_description, crate_name, crate_version, Arg, Command},
+    crate::{
+        mint_to::process_mint_to,
+        transfer_to::{process_transfer_from, process_transfer_to},
+    },
+    clap::{crate_description, crate_name, crate_version, Arg, ArgMatches, Command},
     create_token::process_create_token,
     dialoguer::{Confirm, Input},
-    mpl_token_metadata::{
-        accounts::Metadata, instructions::UpdateMetadataAccountV2Builder, types::DataV2,
-    },
     solana_clap_v3_utils::{
-        input_parsers::{parse_url_or_moniker, pubkey_of},
-        input_validators::{is_valid_signer, normalize_to_url_if_moniker},
-        keypair::DefaultSigner,
+        input_parsers::{lamports_of_sol, parse_url_or_moniker, pubkey_of, pubkeys_of, value_of},
+        input_validators::{
+            is_amount, is_hash, is_pubkey_sig, is_valid_pubkey, is_valid_signer,
+            normalize_to_url_if_moniker,
+        },
+        keypair::{signer_from_path, DefaultSigner},
+        nonce::{NONCE_ARG, NONCE_AUTHORITY_ARG},
+        offline::{BLOCKHASH_ARG, SIGNER_ARG, SIGN_ONLY_ARG},
     },
     solana_client::nonblocking::rpc_client::RpcClient,
     solana_remote_wallet::remote_wallet::RemoteWalletManager,
     solana_sdk::{
-        commitment_config::CommitmentConfig,
-        signature::Keypair,
-        signer::{EncodableKey, Signer},
+        commitment_config::CommitmentConfig, native_token::lamports_to_sol, signature::Keypair,
+        signer::Signer,
     },
     std::{process::exit, rc::Rc},
 };
 
 pub mod add_liquidity;
+pub mod amount;
 pub mod authorities;
+pub mod burn;
+pub mod close_accounts;
 pub mod create_token;
+pub mod delegate;
+pub mod distribute;
+pub mod extensions;
 pub mod freeze;
+pub mod memo;
+pub mod metadata;
+pub mod metadata_diff;
+pub mod metadata_json;
 pub mod mint_to;
+pub mod nonce_account;
+pub mod output;
+pub mod program_errors;
+pub mod token_account;
+pub mod token_program;
+pub mod transaction;
+pub mod transfer_fee;
 pub mod transfer_to;
 pub mod unfreeze;
 pub mod update_metadata;
 pub mod utils;
+pub mod verify;
+
+/// Rows per `distribute` transaction, small enough for Token Metadata transfers to fit.
+const DEFAULT_BATCH_SIZE_ARG: &str = "5";
+const DEFAULT_CONCURRENCY_ARG: &str = "4";
 
 struct Config {
     commitment_config: CommitmentConfig,
     default_signer: Box<dyn Signer>,
+    fee_payer: Option<Box<dyn Signer>>,
     json_rpc_url: String,
+    output_format: OutputFormat,
+    token_program: Option<TokenProgram>,
+    transaction_config: TransactionConfig,
     verbose: bool,
     websocket_url: String,
 }
 
+impl Config {
+    fn fee_payer(&self) -> &dyn Signer {
+        self.fee_payer
+            .as_deref()
+            .unwrap_or_else(|| self.default_signer.as_ref())
+    }
+}
+
+/// Reads the Token-2022 extensions for a new mint from `--extensions-config`, with the
+/// extension flags taking precedence over the file.
+fn mint_extensions_of(matches: &ArgMatches) -> Result<MintExtensions, Box<dyn std::error::Error>> {
+    let mut extensions: MintExtensions = match matches.value_of("extensions_config") {
+        Some(path) => read_config_file(path)?,
+        None => MintExtensions::default(),
+    };
+
+    if let (Some(basis_points), Some(maximum_fee)) = (
+        matches.get_one::<u16>("transfer_fee_basis_points"),
+        matches.get_one::<u64>("transfer_fee_maximum"),
+    ) {
+        extensions.transfer_fee = Some(TransferFee {
+            basis_points: *basis_points,
+            maximum_fee: *maximum_fee,
+        });
+    }
+    if matches.is_present("metadata_pointer") {
+        extensions.metadata_pointer = true;
+    }
+    if let Some(delegate) = pubkey_of(matches, "permanent_delegate") {
+        extensions.permanent_delegate = Some(delegate);
+    }
+    if let Some(state) = matches.get_one::<DefaultAccountState>("default_account_state") {
+        extensions.default_account_state = Some(*state);
+    }
+    if matches.is_present("non_transferable") {
+        extensions.non_transferable = true;
+    }
+    if let Some(rate) = matches.get_one::<i16>("interest_rate") {
+        extensions.interest_rate = Some(*rate);
+    }
+    if let Some(close_authority) = pubkey_of(matches, "close_authority") {
+        extensions.close_authority = Some(close_authority);
+    }
+    Ok(extensions)
+}
+
+/// Reads the metadata changes from `--from-file`, with the `updateMetadata` flags taking
+/// precedence over the file.
+fn metadata_patch_of(matches: &ArgMatches) -> Result<MetadataPatch, Box<dyn std::error::Error>> {
+    let mut patch: MetadataPatch = match matches.value_of("from_file") {
+        Some(path) => read_config_file(path)?,
+        None => MetadataPatch::default(),
+    };
+
+    if let Some(name) = matches.get_one::<String>("name") {
+        patch.name = Some(name.clone());
+    }
+    if let Some(symbol) = matches.get_one::<String>("symbol") {
+        patch.symbol = Some(symbol.clone());
+    }
+    if let Some(uri) = matches.get_one::<String>("uri") {
+        patch.uri = Some(uri.clone());
+    }
+    if let Some(basis_points) = matches.get_one::<u16>("seller_fee_basis_points") {
+        patch.seller_fee_basis_points = Some(*basis_points);
+    }
+    if let Some(creators) = matches.get_many::<CreatorPatch>("creator") {
+        patch.creators = Some(creators.cloned().collect());
+    }
+    if let Some(creators) = matches.get_many::<CreatorPatch>("add_creator") {
+        patch.add_creators = creators.cloned().collect();
+    }
+    if let Some(creators) = pubkeys_of(matches, "remove_creator") {
+        patch.remove_creators = creators;
+    }
+    if let Some(creators) = pubkeys_of(matches, "creator_order") {
+        patch.creator_order = creators;
+    }
+    if let Some(collection) = pubkey_of(matches, "collection") {
+        patch.collection = Some(collection);
+        patch.clear_collection = false;
+    }
+    if matches.is_present("clear_collection") {
+        patch.collection = None;
+        patch.clear_collection = true;
+    }
+    if let Some(uses) = matches.get_one::<UsesPatch>("uses") {
+        patch.uses = Some(uses.clone());
+        patch.clear_uses = false;
+    }
+    if matches.is_present("clear_uses") {
+        patch.uses = None;
+        patch.clear_uses = true;
+    }
+    if matches.is_present("immutable") {
+        patch.is_mutable = Some(false);
+    }
+    if let Some(new_update_authority) = pubkey_of(matches, "new_update_authority") {
+        patch.new_update_authority = Some(new_update_authority);
+    }
+    if matches.is_present("primary_sale_happened") {
+        patch.primary_sale_happened = Some(true);
+    }
+    if let Some(token_standard) = matches.get_one::<TokenStandardArg>("token_standard") {
+        patch.token_standard = Some(*token_standard);
+    }
+    if let Some(rule_set) = pubkey_of(matches, "rule_set") {
+        patch.rule_set = Some(rule_set);
+        patch.clear_rule_set = false;
+    }
+    if matches.is_present("clear_rule_set") {
+        patch.rule_set = None;
+        patch.clear_rule_set = true;
+    }
+    Ok(patch)
+}
+
+/// Reads the `amount` argument, in base units when `--raw` is given.
+fn amount_of(matches: &ArgMatches) -> Result<Amount, String> {
+    let amount = matches.get_one::<String>("amount").unwrap();
+    if matches.is_present("raw") {
+        amount
+            .parse()
+            .map(Amount::Raw)
+            .map_err(|_| format!("invalid base unit amount `{amount}`"))
+    } else {
+        Ok(Amount::Ui(amount.clone()))
+    }
+}
+
+/// Reads the `--memo` and `--reference` arguments.
+fn annotation_of(matches: &ArgMatches) -> Annotation {
+    Annotation {
+        memo: matches.get_one::<String>("memo").cloned(),
+        references: pubkeys_of(matches, "reference").unwrap_or_default(),
+    }
+}
+
+/// Reads `--with-metadata` and `--no-metadata`; `None` means neither was given.
+fn metadata_account_of(matches: &ArgMatches) -> Option<bool> {
+    if matches.is_present("with_metadata") {
+        Some(true)
+    } else if matches.is_present("no_metadata") {
+        Some(false)
+    } else {
+        None
+    }
+}
+
+fn print_report<T: Report>(report: &T, output_format: OutputFormat) {
+    output_format.print(report);
+    if !report.is_success() {
+        exit(1);
+    }
+}
+
+/// Prints the reports of a command sent in several transactions, as a list in JSON.
+fn print_reports<T: Report>(reports: &[T], output_format: OutputFormat) {
+    output_format.print_all(reports);
+    if reports.iter().any(|report| !report.is_success()) {
+        exit(1);
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let app_matches = Command::new(crate_name!())
@@ -84,6 +301,95 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .global(true)
                 .help("Show additional information"),
         )
+        .arg(
+            Arg::new("skip_preflight")
+                .long("skip-preflight")
+                .takes_value(false)
+                .global(true)
+                .help("Send transactions without simulating them first"),
+        )
+        .arg(
+            Arg::new("dry_run")
+                .long("dry-run")
+                .takes_value(false)
+                .global(true)
+                .help("Print and simulate transactions without signing or sending them"),
+        )
+        .arg(
+            Arg::new("compute_unit_price")
+                .long("compute-unit-price")
+                .value_name("MICRO_LAMPORTS")
+                .takes_value(true)
+                .global(true)
+                .value_parser(ComputeBudgetSetting::<u64>::from_str)
+                .help("Priority fee per compute unit, or `auto` to use recent fees for these accounts"),
+        )
+        .arg(
+            Arg::new("compute_unit_limit")
+                .long("compute-unit-limit")
+                .value_name("UNITS")
+                .takes_value(true)
+                .global(true)
+                .value_parser(ComputeBudgetSetting::<u32>::from_str)
+                .help("Compute unit limit, or `auto` to use the simulated units plus a margin"),
+        )
+        .arg(
+            Arg::new(SIGN_ONLY_ARG.name)
+                .long(SIGN_ONLY_ARG.long)
+                .takes_value(false)
+                .global(true)
+                .requires(BLOCKHASH_ARG.name)
+                .conflicts_with("dry_run")
+                .help("Sign the transaction offline and print the signatures without sending it"),
+        )
+        .arg(
+            Arg::new(BLOCKHASH_ARG.name)
+                .long(BLOCKHASH_ARG.long)
+                .value_name("BLOCKHASH")
+                .takes_value(true)
+                .global(true)
+                .validator(|s| is_hash(s))
+                .help(BLOCKHASH_ARG.help),
+        )
+        .arg(
+            Arg::new(SIGNER_ARG.name)
+                .long(SIGNER_ARG.long)
+                .value_name("PUBKEY=SIGNATURE")
+                .takes_value(true)
+                .global(true)
+                .multiple_occurrences(true)
+                .requires(BLOCKHASH_ARG.name)
+                .validator(|s| is_pubkey_sig(s))
+                .help("Provide a public-key/signature pair from a --sign-only run"),
+        )
+        .arg(
+            Arg::new("fee_payer")
+                .long("fee-payer")
+                .value_name("KEYPAIR")
+                .takes_value(true)
+                .global(true)
+                .validator(|s| is_valid_signer(s))
+                .help("Keypair paying transaction fees and rent [default: client keypair]"),
+        )
+        .arg(
+            Arg::new(NONCE_ARG.name)
+                .long(NONCE_ARG.long)
+                .value_name("PUBKEY")
+                .takes_value(true)
+                .global(true)
+                .validator(|s| is_valid_pubkey(s))
+                .help("Use the nonce account as the blockhash and advance it in the transaction"),
+        )
+        .arg(
+            Arg::new(NONCE_AUTHORITY_ARG.name)
+                .long(NONCE_AUTHORITY_ARG.long)
+                .value_name("KEYPAIR")
+                .takes_value(true)
+                .global(true)
+                .requires(NONCE_ARG.name)
+                .validator(|s| is_valid_signer(s))
+                .help("Nonce authority [default: client keypair]"),
+        )
         .arg(
             Arg::new("json_rpc_url")
                 .short('u')
@@ -94,6 +400,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 .value_parser(parse_url_or_moniker)
                 .help("JSON RPC URL for the cluster [default: value from configuration file]"),
         )
+        .arg(
+            Arg::new("token_program")
+                .long("token-program")
+                .value_name("PROGRAM")
+                .takes_value(true)
+                .global(true)
+                .value_parser(TokenProgram::from_str)
+                .help(
+                    "Token program of the mint: legacy or 2022 [default: legacy for new mints, \
+                     detected from the mint otherwise]",
+                ),
+        )
+        .arg(
+            Arg::new("output_format")
+                .long("output")
+                .value_name("FORMAT")
+                .takes_value(true)
+                .global(true)
+                .value_parser(OutputFormat::from_str)
+                .default_value("display")
+                .help("Return information in specified output format: display, json, json-compact"),
+        )
         .subcommand(
             Command::new("createTokenWithMetadata")
                 .about("Creates a new token with metadata")
@@ -126,10 +454,100 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         .help("URI"),
                 )
                 .arg(
-                    Arg::new("mint_authority")
-                        .value_name("MINT_AUTHORITY")
+                    Arg::new("metadata_file")
+                        .long("metadata-file")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .help("Local copy of the JSON at URI to validate before creating"),
+                )
+                .arg(
+                    Arg::new("mint_keypair")
+                        .long("mint-keypair")
+                        .value_name("KEYPAIR")
+                        .takes_value(true)
+                        .validator(|s| is_valid_signer(s))
+                        .help("Mint keypair, any signer source [default: generate a new keypair]"),
+                )
+                .arg(
+                    Arg::new("save_mint_keypair")
+                        .long("save-mint-keypair")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .conflicts_with("mint_keypair")
+                        .help("Write the generated mint keypair to PATH, which must not exist"),
+                )
+                .arg(
+                    Arg::new("extensions_config")
+                        .long("extensions-config")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .help("JSON or TOML file with Token-2022 extensions, overridden by flags"),
+                )
+                .arg(
+                    Arg::new("transfer_fee_basis_points")
+                        .long("transfer-fee-basis-points")
+                        .value_name("BPS")
+                        .takes_value(true)
+                        .requires("transfer_fee_maximum")
+                        .value_parser(clap::value_parser!(u16))
+                        .help("Transfer fee in basis points"),
+                )
+                .arg(
+                    Arg::new("transfer_fee_maximum")
+                        .long("transfer-fee-maximum")
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .requires("transfer_fee_basis_points")
+                        .value_parser(clap::value_parser!(u64))
+                        .help("Maximum transfer fee, in base units"),
+                )
+                .arg(
+                    Arg::new("metadata_pointer")
+                        .long("metadata-pointer")
+                        .takes_value(false)
+                        .help(
+                            "Store the metadata in the mint with the token-metadata interface \
+                             instead of a Token Metadata account",
+                        ),
+                )
+                .arg(
+                    Arg::new("permanent_delegate")
+                        .long("permanent-delegate")
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Permanent delegate over every account of the mint"),
+                )
+                .arg(
+                    Arg::new("default_account_state")
+                        .long("default-account-state")
+                        .value_name("STATE")
+                        .takes_value(true)
+                        .value_parser(DefaultAccountState::from_str)
+                        .help("State of new token accounts: initialized or frozen"),
+                )
+                .arg(
+                    Arg::new("non_transferable")
+                        .long("non-transferable")
+                        .takes_value(false)
+                        .help("Make the token non-transferable"),
+                )
+                .arg(
+                    Arg::new("interest_rate")
+                        .long("interest-rate")
+                        .value_name("BPS")
+                        .takes_value(true)
+                        .allow_hyphen_values(true)
+                        .value_parser(clap::value_parser!(i16))
+                        .help("Interest rate in basis points"),
+                )
+                .arg(
+                    Arg::new("close_authority")
+                        .long("close-authority")
+                        .value_name("PUBKEY")
                         .takes_value(true)
-                        .help("Mint Authority (leave blank to generate new keypair)"),
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Authority allowed to close the mint"),
                 ),
         )
         .subcommand(
@@ -154,7 +572,53 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         .required(true)
                         .value_name("AMOUNT")
                         .takes_value(true)
-                        .help("Amount to mint"),
+                        .help("Amount to mint, in tokens like 1500.25"),
+                )
+                .arg(
+                    Arg::new("raw")
+                        .long("raw")
+                        .takes_value(false)
+                        .help("Read AMOUNT in base units instead of tokens"),
+                )
+                .arg(
+                    Arg::new("mint_decimals")
+                        .long("mint-decimals")
+                        .value_name("DECIMALS")
+                        .takes_value(true)
+                        .value_parser(clap::value_parser!(u8))
+                        .help("Mint decimals, skips the lookup; required with --sign-only"),
+                )
+                .arg(
+                    Arg::new("with_metadata")
+                        .long("with-metadata")
+                        .takes_value(false)
+                        .conflicts_with("no_metadata")
+                        .help(
+                            "Mint through the Token Metadata account without looking it up; \
+                             Token-2022 mints need this or --no-metadata with --sign-only",
+                        ),
+                )
+                .arg(
+                    Arg::new("no_metadata")
+                        .long("no-metadata")
+                        .takes_value(false)
+                        .help("Mint directly through the token program without looking up metadata"),
+                )
+                .arg(
+                    Arg::new("memo")
+                        .long("memo")
+                        .value_name("TEXT")
+                        .takes_value(true)
+                        .help("Add a memo signed by the authority"),
+                )
+                .arg(
+                    Arg::new("reference")
+                        .long("reference")
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .multiple_occurrences(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Add a read-only reference key to look the transaction up by"),
                 ),
         )
         .subcommand(
@@ -179,90 +643,795 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         .required(true)
                         .value_name("AMOUNT")
                         .takes_value(true)
-                        .help("Amount to transfer"),
+                        .help("Amount to transfer, in tokens like 1500.25"),
+                )
+                .arg(
+                    Arg::new("raw")
+                        .long("raw")
+                        .takes_value(false)
+                        .help("Read AMOUNT in base units instead of tokens"),
+                )
+                .arg(
+                    Arg::new("mint_decimals")
+                        .long("mint-decimals")
+                        .value_name("DECIMALS")
+                        .takes_value(true)
+                        .value_parser(clap::value_parser!(u8))
+                        .help("Mint decimals, skips the lookup; required with --sign-only"),
+                )
+                .arg(
+                    Arg::new("memo")
+                        .long("memo")
+                        .value_name("TEXT")
+                        .takes_value(true)
+                        .help("Add a memo signed by the authority"),
+                )
+                .arg(
+                    Arg::new("reference")
+                        .long("reference")
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .multiple_occurrences(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Add a read-only reference key to look the transaction up by"),
                 ),
         )
         .subcommand(
-            Command::new("freeze")
-                .about("Freeze an account")
+            Command::new("transferFrom")
+                .about("Transfer tokens from an owner's account as its delegate")
                 .arg(
-                    Arg::new("account")
+                    Arg::new("owner")
                         .required(true)
-                        .value_name("ACCOUNT")
+                        .value_name("OWNER")
                         .takes_value(true)
-                        .help("Account to freeze"),
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Owner of the source account"),
+                )
+                .arg(
+                    Arg::new("receiver_account")
+                        .required(true)
+                        .value_name("RECEIVER_ACCOUNT")
+                        .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Receiver account"),
                 )
                 .arg(
                     Arg::new("mint_pubkey")
                         .required(true)
                         .value_name("MINT_PUBKEY")
                         .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
                         .help("Mint pubkey"),
+                )
+                .arg(
+                    Arg::new("amount")
+                        .required(true)
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .help("Amount to transfer, in tokens like 1500.25"),
+                )
+                .arg(
+                    Arg::new("raw")
+                        .long("raw")
+                        .takes_value(false)
+                        .help("Read AMOUNT in base units instead of tokens"),
+                )
+                .arg(
+                    Arg::new("mint_decimals")
+                        .long("mint-decimals")
+                        .value_name("DECIMALS")
+                        .takes_value(true)
+                        .value_parser(clap::value_parser!(u8))
+                        .help("Mint decimals, skips the lookup; required with --sign-only"),
                 ),
         )
         .subcommand(
-            Command::new("unfreeze")
-                .about("Unfreeze an account")
+            Command::new("approve")
+                .about("Allow a delegate to spend from the signer's account")
                 .arg(
-                    Arg::new("account")
+                    Arg::new("delegate")
                         .required(true)
-                        .value_name("ACCOUNT")
+                        .value_name("DELEGATE")
                         .takes_value(true)
-                        .help("Account to unfreeze"),
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Delegate allowed to spend"),
                 )
                 .arg(
                     Arg::new("mint_pubkey")
                         .required(true)
                         .value_name("MINT_PUBKEY")
                         .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
                         .help("Mint pubkey"),
+                )
+                .arg(
+                    Arg::new("amount")
+                        .required(true)
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .help("Amount to allow, in tokens like 1500.25"),
+                )
+                .arg(
+                    Arg::new("raw")
+                        .long("raw")
+                        .takes_value(false)
+                        .help("Read AMOUNT in base units instead of tokens"),
+                )
+                .arg(
+                    Arg::new("mint_decimals")
+                        .long("mint-decimals")
+                        .value_name("DECIMALS")
+                        .takes_value(true)
+                        .value_parser(clap::value_parser!(u8))
+                        .help("Mint decimals, skips the lookup; required with --sign-only"),
                 ),
         )
         .subcommand(
-            Command::new("updateMetadata")
-                .about("Updates metadata for a token")
+            Command::new("revoke")
+                .about("Remove the delegate allowance on the signer's account")
                 .arg(
                     Arg::new("mint_pubkey")
                         .required(true)
                         .value_name("MINT_PUBKEY")
                         .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
                         .help("Mint pubkey"),
                 ),
         )
         .subcommand(
-            Command::new("updateAuthorities")
-                .about("Updates authorities for a token (Mint,Freeze,Owner)")
+            Command::new("burn")
+                .about("Burn tokens from the signer's account or, as delegate, from another one")
                 .arg(
                     Arg::new("mint_pubkey")
                         .required(true)
                         .value_name("MINT_PUBKEY")
                         .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
                         .help("Mint pubkey"),
+                )
+                .arg(
+                    Arg::new("amount")
+                        .required(true)
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .help("Amount to burn, in tokens like 1500.25"),
+                )
+                .arg(
+                    Arg::new("raw")
+                        .long("raw")
+                        .takes_value(false)
+                        .help("Read AMOUNT in base units instead of tokens"),
+                )
+                .arg(
+                    Arg::new("from")
+                        .long("from")
+                        .value_name("TOKEN_ACCOUNT")
+                        .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .help(
+                            "Token account to burn from as its delegate or the permanent \
+                             delegate [default: signer's associated token account]",
+                        ),
                 ),
         )
         .subcommand(
-            Command::new("addToLiquidity")
-                .about("Add token supply to bridge contract as liquidity")
+            Command::new("distribute")
+                .about("Send tokens to every recipient of a CSV file")
                 .arg(
-                    Arg::new("amount")
+                    Arg::new("csv")
                         .required(true)
-                        .value_name("AMOUNT")
+                        .value_name("CSV")
                         .takes_value(true)
-                        .help("Amount to add"),
+                        .help("CSV file with recipient,amount[,memo] rows"),
                 )
                 .arg(
-                    Arg::new("mint_of_token_sent")
+                    Arg::new("mint_pubkey")
                         .required(true)
-                        .value_name("MINT_OF_TOKEN_SENT")
-                        .help("Mint of token sent"),
+                        .value_name("MINT_PUBKEY")
+                        .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Mint pubkey"),
                 )
-                .arg(Arg::new("program_id").value_name("PROGRAM_ID").help(
-                    "Bridge Program ID (leave blank to use default declared in program crate)",
-                )),
+                .arg(
+                    Arg::new("state_file")
+                        .long("state-file")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .help("File recording the progress of each row [default: CSV.state.json]"),
+                )
+                .arg(
+                    Arg::new("mint")
+                        .long("mint")
+                        .takes_value(false)
+                        .help("Mint new tokens instead of transferring from the signer"),
+                )
+                .arg(
+                    Arg::new("raw")
+                        .long("raw")
+                        .takes_value(false)
+                        .help("Read amounts in base units instead of tokens"),
+                )
+                .arg(
+                    Arg::new("batch_size")
+                        .long("batch-size")
+                        .value_name("ROWS")
+                        .takes_value(true)
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value(DEFAULT_BATCH_SIZE_ARG)
+                        .help("Rows per transaction"),
+                )
+                .arg(
+                    Arg::new("concurrency")
+                        .long("concurrency")
+                        .value_name("TRANSACTIONS")
+                        .takes_value(true)
+                        .value_parser(clap::value_parser!(usize))
+                        .default_value(DEFAULT_CONCURRENCY_ARG)
+                        .help("Transactions in flight at once"),
+                ),
         )
-        .get_matches();
-
-    let (command, matches) = app_matches.subcommand().unwrap();
+        .subcommand(
+            Command::new("freeze")
+                .about("Freeze an account")
+                .arg(
+                    Arg::new("account")
+                        .required(true)
+                        .value_name("ACCOUNT")
+                        .takes_value(true)
+                        .help("Account to freeze"),
+                )
+                .arg(
+                    Arg::new("mint_pubkey")
+                        .required(true)
+                        .value_name("MINT_PUBKEY")
+                        .takes_value(true)
+                        .help("Mint pubkey"),
+                ),
+        )
+        .subcommand(
+            Command::new("unfreeze")
+                .about("Unfreeze an account")
+                .arg(
+                    Arg::new("account")
+                        .required(true)
+                        .value_name("ACCOUNT")
+                        .takes_value(true)
+                        .help("Account to unfreeze"),
+                )
+                .arg(
+                    Arg::new("mint_pubkey")
+                        .required(true)
+                        .value_name("MINT_PUBKEY")
+                        .takes_value(true)
+                        .help("Mint pubkey"),
+                ),
+        )
+        .subcommand(
+            Command::new("closeAccounts")
+                .about("Close the signer's empty token accounts and reclaim their rent")
+                .arg(
+                    Arg::new("destination")
+                        .long("destination")
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Account receiving the rent [default: signer]"),
+                )
+                .arg(
+                    Arg::new("list")
+                        .long("list")
+                        .takes_value(false)
+                        .help("Only list the signer's token accounts and whether they are empty"),
+                )
+                .arg(
+                    Arg::new("yes")
+                        .long("yes")
+                        .takes_value(false)
+                        .help("Close the accounts without asking for confirmation"),
+                ),
+        )
+        .subcommand(
+            Command::new("harvestFees")
+                .about("Harvest withheld transfer fees from every token account into the mint")
+                .arg(
+                    Arg::new("mint_pubkey")
+                        .required(true)
+                        .value_name("MINT_PUBKEY")
+                        .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Mint pubkey"),
+                ),
+        )
+        .subcommand(
+            Command::new("withdrawFees")
+                .about("Withdraw withheld transfer fees to a treasury token account")
+                .arg(
+                    Arg::new("mint_pubkey")
+                        .required(true)
+                        .value_name("MINT_PUBKEY")
+                        .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Mint pubkey"),
+                )
+                .arg(
+                    Arg::new("treasury")
+                        .required(true)
+                        .value_name("TREASURY")
+                        .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Owner of the token account receiving the fees"),
+                )
+                .arg(
+                    Arg::new("from_accounts")
+                        .long("from-accounts")
+                        .takes_value(false)
+                        .help("Withdraw from the token accounts instead of the mint"),
+                ),
+        )
+        .subcommand(
+            Command::new("updateTransferFee")
+                .about("Update the transfer fee of a mint")
+                .arg(
+                    Arg::new("mint_pubkey")
+                        .required(true)
+                        .value_name("MINT_PUBKEY")
+                        .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Mint pubkey"),
+                )
+                .arg(
+                    Arg::new("basis_points")
+                        .required(true)
+                        .value_name("BPS")
+                        .takes_value(true)
+                        .value_parser(clap::value_parser!(u16))
+                        .help("Transfer fee in basis points"),
+                )
+                .arg(
+                    Arg::new("maximum_fee")
+                        .required(true)
+                        .value_name("MAXIMUM_FEE")
+                        .takes_value(true)
+                        .value_parser(clap::value_parser!(u64))
+                        .help("Maximum transfer fee, in base units"),
+                ),
+        )
+        .subcommand(
+            Command::new("updateMetadata")
+                .about("Updates metadata for a token")
+                .arg(
+                    Arg::new("mint_pubkey")
+                        .required(true)
+                        .value_name("MINT_PUBKEY")
+                        .takes_value(true)
+                        .help("Mint pubkey"),
+                )
+                .arg(
+                    Arg::new("from_file")
+                        .long("from-file")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .help("JSON or TOML patch with the fields to change"),
+                )
+                .arg(
+                    Arg::new("name")
+                        .long("name")
+                        .value_name("NAME")
+                        .takes_value(true)
+                        .help("New token name"),
+                )
+                .arg(
+                    Arg::new("symbol")
+                        .long("symbol")
+                        .value_name("SYMBOL")
+                        .takes_value(true)
+                        .help("New token symbol"),
+                )
+                .arg(
+                    Arg::new("uri")
+                        .long("uri")
+                        .value_name("URI")
+                        .takes_value(true)
+                        .help("New metadata URI"),
+                )
+                .arg(
+                    Arg::new("seller_fee_basis_points")
+                        .long("seller-fee-basis-points")
+                        .value_name("BASIS_POINTS")
+                        .takes_value(true)
+                        .value_parser(clap::value_parser!(u16))
+                        .help("New seller fee in basis points"),
+                )
+                .arg(
+                    Arg::new("creator")
+                        .long("creator")
+                        .value_name("ADDRESS:SHARE")
+                        .takes_value(true)
+                        .multiple_occurrences(true)
+                        .value_parser(clap::value_parser!(CreatorPatch))
+                        .help("Creator with its share, repeated to replace the creator list"),
+                )
+                .arg(
+                    Arg::new("add_creator")
+                        .long("add-creator")
+                        .value_name("ADDRESS:SHARE")
+                        .takes_value(true)
+                        .multiple_occurrences(true)
+                        .value_parser(clap::value_parser!(CreatorPatch))
+                        .help("Append a creator, or change the share of an existing one"),
+                )
+                .arg(
+                    Arg::new("remove_creator")
+                        .long("remove-creator")
+                        .value_name("ADDRESS")
+                        .takes_value(true)
+                        .multiple_occurrences(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Remove a creator"),
+                )
+                .arg(
+                    Arg::new("creator_order")
+                        .long("creator-order")
+                        .value_name("ADDRESS")
+                        .takes_value(true)
+                        .multiple_occurrences(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Reorder the creators, repeated once for each of them in order"),
+                )
+                .arg(
+                    Arg::new("collection")
+                        .long("collection")
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .conflicts_with("clear_collection")
+                        .help("Collection mint"),
+                )
+                .arg(
+                    Arg::new("clear_collection")
+                        .long("clear-collection")
+                        .takes_value(false)
+                        .help("Remove the collection"),
+                )
+                .arg(
+                    Arg::new("uses")
+                        .long("uses")
+                        .value_name("METHOD:REMAINING:TOTAL")
+                        .takes_value(true)
+                        .value_parser(clap::value_parser!(UsesPatch))
+                        .conflicts_with("clear_uses")
+                        .help("Uses, with METHOD one of burn, multiple or single"),
+                )
+                .arg(
+                    Arg::new("clear_uses")
+                        .long("clear-uses")
+                        .takes_value(false)
+                        .help("Remove the uses"),
+                )
+                .arg(
+                    Arg::new("immutable")
+                        .long("immutable")
+                        .takes_value(false)
+                        .help("Make the metadata immutable"),
+                )
+                .arg(
+                    Arg::new("new_update_authority")
+                        .long("new-update-authority")
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Transfer the update authority"),
+                )
+                .arg(
+                    Arg::new("primary_sale_happened")
+                        .long("primary-sale-happened")
+                        .takes_value(false)
+                        .help("Mark the primary sale as happened"),
+                )
+                .arg(
+                    Arg::new("token_standard")
+                        .long("token-standard")
+                        .value_name("STANDARD")
+                        .takes_value(true)
+                        .value_parser(clap::value_parser!(TokenStandardArg))
+                        .help(
+                            "Token standard: fungible, fungible_asset, non_fungible, \
+                             non_fungible_edition, programmable_non_fungible or \
+                             programmable_non_fungible_edition",
+                        ),
+                )
+                .arg(
+                    Arg::new("rule_set")
+                        .long("rule-set")
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .conflicts_with("clear_rule_set")
+                        .help("Authorization rule set of a programmable NFT"),
+                )
+                .arg(
+                    Arg::new("clear_rule_set")
+                        .long("clear-rule-set")
+                        .takes_value(false)
+                        .help("Remove the rule set"),
+                )
+                .arg(
+                    Arg::new("yes")
+                        .long("yes")
+                        .takes_value(false)
+                        .help("Update without asking for confirmation"),
+                )
+                .arg(
+                    Arg::new("metadata_file")
+                        .long("metadata-file")
+                        .value_name("PATH")
+                        .takes_value(true)
+                        .help("Local copy of the JSON at the URI to validate before updating"),
+                ),
+        )
+        .subcommand(
+            Command::new("signMetadata")
+                .about("Verify the signer as a creator of a token")
+                .arg(
+                    Arg::new("mint_pubkey")
+                        .required(true)
+                        .value_name("MINT_PUBKEY")
+                        .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Mint pubkey"),
+                ),
+        )
+        .subcommand(
+            Command::new("removeCreatorVerification")
+                .about("Remove the signer's creator verification from a token")
+                .arg(
+                    Arg::new("mint_pubkey")
+                        .required(true)
+                        .value_name("MINT_PUBKEY")
+                        .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Mint pubkey"),
+                ),
+        )
+        .subcommand(
+            Command::new("verifyCollection")
+                .about("Verify a token as a member of its collection")
+                .arg(
+                    Arg::new("mint_pubkey")
+                        .required(true)
+                        .value_name("MINT_PUBKEY")
+                        .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Mint pubkey"),
+                ),
+        )
+        .subcommand(
+            Command::new("unverifyCollection")
+                .about("Remove the collection verification of a token")
+                .arg(
+                    Arg::new("mint_pubkey")
+                        .required(true)
+                        .value_name("MINT_PUBKEY")
+                        .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Mint pubkey"),
+                ),
+        )
+        .subcommand(
+            Command::new("metadata")
+                .about("Generates and validates token metadata JSON")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("generate")
+                        .about("Generates the metadata JSON of a fungible token")
+                        .arg(
+                            Arg::new("name")
+                                .long("name")
+                                .required(true)
+                                .value_name("NAME")
+                                .takes_value(true)
+                                .help("Token name"),
+                        )
+                        .arg(
+                            Arg::new("symbol")
+                                .long("symbol")
+                                .required(true)
+                                .value_name("SYMBOL")
+                                .takes_value(true)
+                                .help("Token symbol"),
+                        )
+                        .arg(
+                            Arg::new("description")
+                                .long("description")
+                                .value_name("DESCRIPTION")
+                                .takes_value(true)
+                                .default_value("")
+                                .help("Token description"),
+                        )
+                        .arg(
+                            Arg::new("image")
+                                .long("image")
+                                .required(true)
+                                .value_name("URL")
+                                .takes_value(true)
+                                .help("URL of the token image"),
+                        )
+                        .arg(
+                            Arg::new("extension")
+                                .long("extension")
+                                .value_name("KEY=VALUE")
+                                .takes_value(true)
+                                .multiple_occurrences(true)
+                                .validator(|s| {
+                                    s.split_once('=')
+                                        .map(|_| ())
+                                        .ok_or_else(|| format!("expected KEY=VALUE, got {s}"))
+                                })
+                                .help("Extension such as website=https://example.com, repeatable"),
+                        )
+                        .arg(
+                            Arg::new("out")
+                                .long("out")
+                                .value_name("PATH")
+                                .takes_value(true)
+                                .help("Write the JSON to PATH instead of stdout"),
+                        ),
+                )
+                .subcommand(
+                    Command::new("validate")
+                        .about("Validates a metadata JSON file against the standard")
+                        .arg(
+                            Arg::new("path")
+                                .required(true)
+                                .value_name("PATH")
+                                .takes_value(true)
+                                .help("Metadata JSON file"),
+                        )
+                        .arg(
+                            Arg::new("mint")
+                                .long("mint")
+                                .value_name("MINT_PUBKEY")
+                                .takes_value(true)
+                                .validator(|s| is_valid_pubkey(s))
+                                .help("Check the name and symbol against the token's metadata"),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("updateAuthorities")
+                .about("Updates authorities for a token (Mint,Freeze,Owner)")
+                .arg(
+                    Arg::new("mint_pubkey")
+                        .required(true)
+                        .value_name("MINT_PUBKEY")
+                        .takes_value(true)
+                        .help("Mint pubkey"),
+                ),
+        )
+        .subcommand(
+            Command::new("addToLiquidity")
+                .about("Add token supply to bridge contract as liquidity")
+                .arg(
+                    Arg::new("amount")
+                        .required(true)
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .help("Amount to add, in tokens like 1500.25"),
+                )
+                .arg(
+                    Arg::new("raw")
+                        .long("raw")
+                        .takes_value(false)
+                        .help("Read AMOUNT in base units instead of tokens"),
+                )
+                .arg(
+                    Arg::new("mint_decimals")
+                        .long("mint-decimals")
+                        .value_name("DECIMALS")
+                        .takes_value(true)
+                        .value_parser(clap::value_parser!(u8))
+                        .help("Mint decimals, skips the lookup; required with --sign-only"),
+                )
+                .arg(
+                    Arg::new("memo")
+                        .long("memo")
+                        .value_name("TEXT")
+                        .takes_value(true)
+                        .help("Add a memo signed by the authority"),
+                )
+                .arg(
+                    Arg::new("reference")
+                        .long("reference")
+                        .value_name("PUBKEY")
+                        .takes_value(true)
+                        .multiple_occurrences(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Add a read-only reference key to look the transaction up by"),
+                )
+                .arg(
+                    Arg::new("mint_of_token_sent")
+                        .required(true)
+                        .value_name("MINT_OF_TOKEN_SENT")
+                        .help("Mint of token sent"),
+                )
+                .arg(Arg::new("program_id").value_name("PROGRAM_ID").help(
+                    "Bridge Program ID (leave blank to use default declared in program crate)",
+                )),
+        )
+        .subcommand(
+            Command::new("createNonceAccount")
+                .about("Create a durable nonce account owned by the signer")
+                .arg(
+                    Arg::new("nonce_account_keypair")
+                        .required(true)
+                        .value_name("NONCE_ACCOUNT_KEYPAIR")
+                        .takes_value(true)
+                        .validator(|s| is_valid_signer(s))
+                        .help("Keypair of the new nonce account"),
+                )
+                .arg(
+                    Arg::new("amount")
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .validator(|s| is_amount(s))
+                        .help("SOL to fund the account with (leave blank for the rent exempt minimum)"),
+                ),
+        )
+        .subcommand(
+            Command::new("showNonceAccount")
+                .about("Show the blockhash, authority and balance of a nonce account")
+                .arg(
+                    Arg::new("nonce_account")
+                        .required(true)
+                        .value_name("NONCE_ACCOUNT")
+                        .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Nonce account"),
+                ),
+        )
+        .subcommand(
+            Command::new("advanceNonceAccount")
+                .about("Advance the blockhash stored in a nonce account")
+                .arg(
+                    Arg::new("nonce_account")
+                        .required(true)
+                        .value_name("NONCE_ACCOUNT")
+                        .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Nonce account"),
+                ),
+        )
+        .subcommand(
+            Command::new("withdrawFromNonceAccount")
+                .about("Withdraw SOL from a nonce account")
+                .arg(
+                    Arg::new("nonce_account")
+                        .required(true)
+                        .value_name("NONCE_ACCOUNT")
+                        .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Nonce account"),
+                )
+                .arg(
+                    Arg::new("destination")
+                        .required(true)
+                        .value_name("DESTINATION")
+                        .takes_value(true)
+                        .validator(|s| is_valid_pubkey(s))
+                        .help("Account receiving the SOL"),
+                )
+                .arg(
+                    Arg::new("amount")
+                        .required(true)
+                        .value_name("AMOUNT")
+                        .takes_value(true)
+                        .validator(|s| is_amount(s))
+                        .help("SOL to withdraw"),
+                ),
+        )
+        .get_matches();
+
+    let (command, matches) = app_matches.subcommand().unwrap();
     let mut wallet_manager: Option<Rc<RemoteWalletManager>> = None;
 
     let config = {
@@ -288,9 +1457,51 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
         let websocket_url = solana_cli_config::Config::compute_websocket_url(&json_rpc_url);
 
+        let fee_payer = matches.value_of("fee_payer").map(|path| {
+            signer_from_path(matches, path, "fee_payer", &mut wallet_manager).unwrap_or_else(
+                |err| {
+                    eprintln!("error: {err}");
+                    exit(1);
+                },
+            )
+        });
+
+        let nonce = pubkey_of(matches, NONCE_ARG.name).map(|account| {
+            let authority_path = matches
+                .value_of(NONCE_AUTHORITY_ARG.name)
+                .unwrap_or(&default_signer.path);
+            let authority = signer_from_path(
+                matches,
+                authority_path,
+                NONCE_AUTHORITY_ARG.name,
+                &mut wallet_manager,
+            )
+            .unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+            NonceConfig { account, authority }
+        });
+
         Config {
             commitment_config: CommitmentConfig::confirmed(),
+            fee_payer,
             json_rpc_url,
+            output_format: *matches.get_one::<OutputFormat>("output_format").unwrap(),
+            token_program: matches.get_one::<TokenProgram>("token_program").copied(),
+            transaction_config: TransactionConfig {
+                skip_preflight: matches.is_present("skip_preflight"),
+                dry_run: matches.is_present("dry_run"),
+                compute_unit_price: matches
+                    .get_one::<ComputeBudgetSetting<u64>>("compute_unit_price")
+                    .copied(),
+                compute_unit_limit: matches
+                    .get_one::<ComputeBudgetSetting<u32>>("compute_unit_limit")
+                    .copied(),
+                sign_only: matches.is_present(SIGN_ONLY_ARG.name),
+                blockhash: value_of(matches, BLOCKHASH_ARG.name),
+                nonce,
+            },
             verbose: matches.is_present("verbose"),
             websocket_url,
             default_signer: default_signer
@@ -304,8 +1515,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     solana_logger::setup_with_default("solana=info");
 
     if config.verbose {
-        println!("JSON RPC URL: {}", config.json_rpc_url);
-        println!("Websocket URL: {}", config.websocket_url);
+        eprintln!("JSON RPC URL: {}", config.json_rpc_url);
+        eprintln!("Websocket URL: {}", config.websocket_url);
     }
 
     let rpc_client =
@@ -318,21 +1529,287 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             let symbol = arg_matches.get_one::<String>("symbol").unwrap();
             let uri = arg_matches.get_one::<String>("uri").unwrap();
 
-            let mint_keypair =
-                if let Some(mint_keypair) = arg_matches.get_one::<String>("mint_authority") {
-                    Keypair::read_from_file(mint_keypair).unwrap()
+            check_onchain_fields(name, symbol, uri).unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+            if let Some(path) = arg_matches.value_of("metadata_file") {
+                validate_metadata_file(path, Some((name, symbol))).unwrap_or_else(|err| {
+                    eprintln!("error: {err}");
+                    exit(1);
+                });
+            }
+
+            let mint: Box<dyn Signer> =
+                if let Some(mint_path) = arg_matches.value_of("mint_keypair") {
+                    signer_from_path(arg_matches, mint_path, "mint_keypair", &mut wallet_manager)
+                        .unwrap_or_else(|err| {
+                            eprintln!("error: {err}");
+                            exit(1);
+                        })
                 } else {
-                    Keypair::new()
+                    // Every run would generate another mint, so the submitting run could never
+                    // reproduce the signed transaction.
+                    if config.transaction_config.sign_only {
+                        eprintln!("error: --sign-only requires --mint-keypair");
+                        exit(1);
+                    }
+                    let mint_keypair = Keypair::new();
+                    // Save the keypair before sending so it is never lost with a landed mint.
+                    // A dry run sends nothing, so there is nothing to keep.
+                    if let Some(path) = arg_matches
+                        .value_of("save_mint_keypair")
+                        .filter(|_| !config.transaction_config.dry_run)
+                    {
+                        write_keypair_file_new(&mint_keypair, path).unwrap_or_else(|err| {
+                            eprintln!("error: {err}");
+                            exit(1);
+                        });
+                        eprintln!("Wrote mint keypair to {path}");
+                    }
+                    Box::new(mint_keypair)
                 };
 
-            let signature = process_create_token(
+            let extensions = mint_extensions_of(arg_matches).unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+            // Extensions only exist in Token-2022, so they select it unless told otherwise.
+            let token_program = config.token_program.unwrap_or(if extensions.is_empty() {
+                TokenProgram::Legacy
+            } else {
+                TokenProgram::Token2022
+            });
+
+            let report = process_create_token(
                 &rpc_client,
+                &config.transaction_config,
                 config.default_signer.as_ref(),
-                mint_keypair,
+                config.fee_payer(),
+                mint.as_ref(),
                 decimals.parse::<u8>().unwrap(),
                 name.clone(),
                 symbol.clone(),
                 uri.clone(),
+                token_program,
+                &extensions,
+            )
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+
+            print_report(&report, config.output_format);
+        }
+        ("mintTo", arg_matches) => {
+            let receiver_account = pubkey_of(arg_matches, "receiver_account").unwrap();
+            let mint_pubkey = pubkey_of(arg_matches, "mint_pubkey").unwrap();
+            let amount = amount_of(arg_matches).unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+
+            let report = process_mint_to(
+                &rpc_client,
+                &config.transaction_config,
+                config.default_signer.as_ref(),
+                config.fee_payer(),
+                mint_pubkey,
+                receiver_account,
+                &amount,
+                config.token_program,
+                arg_matches.get_one::<u8>("mint_decimals").copied(),
+                metadata_account_of(arg_matches),
+                &annotation_of(arg_matches),
+            )
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+
+            print_report(&report, config.output_format);
+        }
+
+        ("transferTo", arg_matches) => {
+            let receiver_account = pubkey_of(arg_matches, "receiver_account").unwrap();
+            let amount = amount_of(arg_matches).unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+            let mint_pubkey = pubkey_of(arg_matches, "mint_pubkey").unwrap();
+
+            let report = process_transfer_to(
+                &rpc_client,
+                &config.transaction_config,
+                config.default_signer.as_ref(),
+                config.fee_payer(),
+                mint_pubkey,
+                receiver_account,
+                &amount,
+                config.token_program,
+                arg_matches.get_one::<u8>("mint_decimals").copied(),
+                &annotation_of(arg_matches),
+            )
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+
+            print_report(&report, config.output_format);
+        }
+        ("transferFrom", arg_matches) => {
+            let owner = pubkey_of(arg_matches, "owner").unwrap();
+            let receiver_account = pubkey_of(arg_matches, "receiver_account").unwrap();
+            let mint_pubkey = pubkey_of(arg_matches, "mint_pubkey").unwrap();
+            let amount = amount_of(arg_matches).unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+
+            let report = process_transfer_from(
+                &rpc_client,
+                &config.transaction_config,
+                config.default_signer.as_ref(),
+                config.fee_payer(),
+                mint_pubkey,
+                owner,
+                receiver_account,
+                &amount,
+                config.token_program,
+                arg_matches.get_one::<u8>("mint_decimals").copied(),
+            )
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+
+            print_report(&report, config.output_format);
+        }
+        ("approve", arg_matches) => {
+            let delegate = pubkey_of(arg_matches, "delegate").unwrap();
+            let mint_pubkey = pubkey_of(arg_matches, "mint_pubkey").unwrap();
+            let amount = amount_of(arg_matches).unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+
+            let report = process_approve(
+                &rpc_client,
+                &config.transaction_config,
+                config.default_signer.as_ref(),
+                config.fee_payer(),
+                mint_pubkey,
+                delegate,
+                &amount,
+                config.token_program,
+                arg_matches.get_one::<u8>("mint_decimals").copied(),
+            )
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+
+            print_report(&report, config.output_format);
+        }
+        ("revoke", arg_matches) => {
+            let mint_pubkey = pubkey_of(arg_matches, "mint_pubkey").unwrap();
+
+            let report = process_revoke(
+                &rpc_client,
+                &config.transaction_config,
+                config.default_signer.as_ref(),
+                config.fee_payer(),
+                mint_pubkey,
+                config.token_program,
+            )
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+
+            print_report(&report, config.output_format);
+        }
+        ("burn", arg_matches) => {
+            let mint_pubkey = pubkey_of(arg_matches, "mint_pubkey").unwrap();
+            let source = pubkey_of(arg_matches, "from");
+            let amount = amount_of(arg_matches).unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+
+            let report = process_burn(
+                &rpc_client,
+                &config.transaction_config,
+                config.default_signer.as_ref(),
+                config.fee_payer(),
+                mint_pubkey,
+                source,
+                &amount,
+                config.token_program,
+            )
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+
+            print_report(&report, config.output_format);
+        }
+        ("distribute", arg_matches) => {
+            let csv_path = arg_matches.get_one::<String>("csv").unwrap();
+            let mint_pubkey = pubkey_of(arg_matches, "mint_pubkey").unwrap();
+
+            let distribution_config = DistributionConfig {
+                csv_path: csv_path.clone(),
+                state_path: arg_matches
+                    .get_one::<String>("state_file")
+                    .cloned()
+                    .unwrap_or_else(|| format!("{csv_path}.state.json")),
+                mode: if arg_matches.is_present("mint") {
+                    DistributionMode::Mint
+                } else {
+                    DistributionMode::Transfer
+                },
+                raw: arg_matches.is_present("raw"),
+                batch_size: *arg_matches.get_one::<usize>("batch_size").unwrap(),
+                concurrency: *arg_matches.get_one::<usize>("concurrency").unwrap(),
+            };
+
+            let report = process_distribute(
+                &rpc_client,
+                &config.transaction_config,
+                config.default_signer.as_ref(),
+                config.fee_payer(),
+                mint_pubkey,
+                &distribution_config,
+                config.token_program,
+            )
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+
+            print_report(&report, config.output_format);
+        }
+        ("freeze", arg_matches) => {
+            let account = pubkey_of(arg_matches, "account").unwrap();
+            let mint_pubkey = pubkey_of(arg_matches, "mint_pubkey").unwrap();
+
+            let report = freeze::process_freeze_account(
+                &rpc_client,
+                &config.transaction_config,
+                config.default_signer.as_ref(),
+                config.fee_payer(),
+                mint_pubkey,
+                account,
+                config.token_program,
             )
             .await
             .unwrap_or_else(|err| {
@@ -340,19 +1817,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 exit(1);
             });
 
-            println!("Signature: {signature}");
+            print_report(&report, config.output_format);
         }
-        ("mintTo", arg_matches) => {
-            let receiver_account = pubkey_of(arg_matches, "receiver_account").unwrap();
+        ("unfreeze", arg_matches) => {
+            let account = pubkey_of(arg_matches, "account").unwrap();
             let mint_pubkey = pubkey_of(arg_matches, "mint_pubkey").unwrap();
-            let amount = arg_matches.get_one::<String>("amount").unwrap();
 
-            let signature = process_mint_to(
+            let report = unfreeze::process_unfreeze_account(
                 &rpc_client,
+                &config.transaction_config,
                 config.default_signer.as_ref(),
+                config.fee_payer(),
                 mint_pubkey,
-                receiver_account,
-                amount.parse::<u64>().unwrap(),
+                account,
+                config.token_program,
             )
             .await
             .unwrap_or_else(|err| {
@@ -360,20 +1838,80 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 exit(1);
             });
 
-            println!("Signature: {signature}");
+            print_report(&report, config.output_format);
         }
+        ("closeAccounts", arg_matches) => {
+            let owner = config.default_signer.pubkey();
+            let destination = pubkey_of(arg_matches, "destination").unwrap_or(owner);
 
-        ("transferTo", arg_matches) => {
-            let receiver_account = pubkey_of(arg_matches, "receiver_account").unwrap();
-            let amount = arg_matches.get_one::<String>("amount").unwrap();
-            let mint_pubkey = pubkey_of(arg_matches, "mint_pubkey").unwrap();
+            let accounts = get_owned_token_accounts(&rpc_client, &owner)
+                .await
+                .unwrap_or_else(|err| {
+                    eprintln!("error: {err}");
+                    exit(1);
+                });
+
+            if arg_matches.is_present("list") {
+                config.output_format.print_all(&accounts);
+                return Ok(());
+            }
+
+            let frozen: Vec<_> = accounts
+                .iter()
+                .filter(|account| account.status == TokenAccountStatus::Frozen)
+                .collect();
+            if !frozen.is_empty() {
+                eprintln!("Frozen empty token accounts, thaw them to close them:");
+                for account in &frozen {
+                    eprintln!("  {} (mint {})", account.pubkey, account.mint);
+                }
+            }
+
+            let closable: Vec<_> = accounts
+                .into_iter()
+                .filter(OwnedTokenAccount::is_closable)
+                .collect();
+            if closable.is_empty() {
+                eprintln!("No empty token accounts");
+                print_reports::<TransactionReport>(&[], config.output_format);
+                return Ok(());
+            }
+
+            eprintln!("Empty token accounts:");
+            for account in &closable {
+                eprintln!("  {} (mint {})", account.pubkey, account.mint);
+            }
+            let rent: u64 = closable.iter().map(|account| account.lamports).sum();
+
+            let transaction_config = &config.transaction_config;
+            if !arg_matches.is_present("yes") && !transaction_config.dry_run {
+                if !std::io::stdin().is_terminal() {
+                    eprintln!(
+                        "error: refusing to close accounts without --yes in a non-interactive \
+                         session"
+                    );
+                    exit(1);
+                }
+                let confirm = Confirm::new()
+                    .with_prompt(format!(
+                        "Close {} token accounts and send {} SOL to {destination}?",
+                        closable.len(),
+                        lamports_to_sol(rent),
+                    ))
+                    .interact()
+                    .unwrap();
+                if !confirm {
+                    return Ok(());
+                }
+            }
 
-            let signature = process_transfer_to(
+            let reports = process_close_accounts(
                 &rpc_client,
+                transaction_config,
                 config.default_signer.as_ref(),
-                mint_pubkey,
-                receiver_account,
-                amount.parse::<u64>().unwrap(),
+                config.fee_payer(),
+                &closable,
+                destination,
             )
             .await
             .unwrap_or_else(|err| {
@@ -381,17 +1919,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 exit(1);
             });
 
-            println!("Signature: {signature}");
+            print_reports(&reports, config.output_format);
         }
-        ("freeze", arg_matches) => {
-            let account = pubkey_of(arg_matches, "account").unwrap();
+        ("harvestFees", arg_matches) => {
             let mint_pubkey = pubkey_of(arg_matches, "mint_pubkey").unwrap();
 
-            let signature = freeze::process_freeze_account(
+            let reports = transfer_fee::process_harvest_withheld_fees(
                 &rpc_client,
-                config.default_signer.as_ref(),
+                &config.transaction_config,
+                config.fee_payer(),
                 mint_pubkey,
-                account,
+                config.token_program,
             )
             .await
             .unwrap_or_else(|err| {
@@ -399,17 +1937,66 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 exit(1);
             });
 
-            println!("Signature: {signature}");
+            if reports.is_empty() {
+                eprintln!("No token accounts with withheld fees");
+            }
+            print_reports(&reports, config.output_format);
         }
-        ("unfreeze", arg_matches) => {
-            let account = pubkey_of(arg_matches, "account").unwrap();
+        ("withdrawFees", arg_matches) => {
+            let mint_pubkey = pubkey_of(arg_matches, "mint_pubkey").unwrap();
+            let treasury = pubkey_of(arg_matches, "treasury").unwrap();
+
+            if arg_matches.is_present("from_accounts") {
+                let reports = transfer_fee::process_withdraw_withheld_fees_from_accounts(
+                    &rpc_client,
+                    &config.transaction_config,
+                    config.default_signer.as_ref(),
+                    config.fee_payer(),
+                    mint_pubkey,
+                    treasury,
+                    config.token_program,
+                )
+                .await
+                .unwrap_or_else(|err| {
+                    eprintln!("error: {err}");
+                    exit(1);
+                });
+
+                if reports.is_empty() {
+                    eprintln!("No token accounts with withheld fees");
+                }
+                print_reports(&reports, config.output_format);
+            } else {
+                let report = transfer_fee::process_withdraw_withheld_fees_from_mint(
+                    &rpc_client,
+                    &config.transaction_config,
+                    config.default_signer.as_ref(),
+                    config.fee_payer(),
+                    mint_pubkey,
+                    treasury,
+                    config.token_program,
+                )
+                .await
+                .unwrap_or_else(|err| {
+                    eprintln!("error: {err}");
+                    exit(1);
+                });
+
+                print_report(&report, config.output_format);
+            }
+        }
+        ("updateTransferFee", arg_matches) => {
             let mint_pubkey = pubkey_of(arg_matches, "mint_pubkey").unwrap();
 
-            let signature = unfreeze::process_unfreeze_account(
+            let report = transfer_fee::process_update_transfer_fee(
                 &rpc_client,
+                &config.transaction_config,
                 config.default_signer.as_ref(),
+                config.fee_payer(),
                 mint_pubkey,
-                account,
+                *arg_matches.get_one::<u16>("basis_points").unwrap(),
+                *arg_matches.get_one::<u64>("maximum_fee").unwrap(),
+                config.token_program,
             )
             .await
             .unwrap_or_else(|err| {
@@ -417,137 +2004,243 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 exit(1);
             });
 
-            println!("Signature: {signature}");
+            print_report(&report, config.output_format);
         }
         ("updateMetadata", arg_matches) => {
             let mint_pubkey = pubkey_of(arg_matches, "mint_pubkey").unwrap();
+            let mut patch = metadata_patch_of(arg_matches).unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
 
-            let (metadata_pubkey, _) = Metadata::find_pda(&mint_pubkey);
-
-            let data = rpc_client
-                .get_account_data(&metadata_pubkey)
+            let (metadata_pubkey, metadata) = get_metadata(&rpc_client, &mint_pubkey)
                 .await
                 .unwrap_or_else(|err| {
                     eprintln!("error: {err}");
                     exit(1);
                 });
 
-            let metadata = Metadata::safe_deserialize(data.as_ref()).unwrap();
-
-            println!("Current metadata:");
-            println!("{:#?}", metadata);
-
-            let mut data: DataV2 = DataV2 {
-                name: metadata.name,
-                symbol: metadata.symbol,
-                uri: metadata.uri,
-                seller_fee_basis_points: metadata.seller_fee_basis_points,
-                creators: metadata.creators,
-                collection: metadata.collection,
-                uses: metadata.uses,
-            };
+            let interactive = std::io::stdin().is_terminal() && !arg_matches.is_present("yes");
+            if patch.is_empty() {
+                if !interactive {
+                    eprintln!("error: nothing to update, pass flags or --from-file");
+                    exit(1);
+                }
+                eprintln!("Current metadata:");
+                print_metadata(&metadata);
+                patch = prompt_metadata_patch();
+            }
 
-            let mut update_metadata_builder = UpdateMetadataAccountV2Builder::new();
+            let update = patch.apply(&metadata).unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
 
-            update_metadata_builder.metadata(metadata_pubkey);
-            update_metadata_builder.update_authority(config.default_signer.pubkey());
+            // Validate what will be on-chain, which is the current data where nothing changes.
+            let data = update.data.clone().unwrap_or_else(|| data_of(&metadata));
+            check_onchain_fields(&data.name, &data.symbol, &data.uri).unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+            if let Some(path) = arg_matches.value_of("metadata_file") {
+                validate_metadata_file(path, Some((&data.name, &data.symbol))).unwrap_or_else(
+                    |err| {
+                        eprintln!("error: {err}");
+                        exit(1);
+                    },
+                );
+            }
 
-            let confirm: bool = Confirm::new()
-                .with_prompt("Do you want to update token metadata?")
-                .interact()
-                .unwrap();
+            print_metadata_diff(&metadata, &update);
 
-            if confirm {
-                let name: String = Input::new()
-                    .with_prompt("Name (leave blank to skip)")
-                    .allow_empty(true)
-                    .interact_text()
-                    .unwrap();
-                if !name.is_empty() {
-                    data.name = name;
+            if !arg_matches.is_present("yes") && !config.transaction_config.dry_run {
+                if !interactive {
+                    eprintln!(
+                        "error: refusing to update metadata without --yes in a non-interactive \
+                         session"
+                    );
+                    exit(1);
                 }
-                let symbol: String = Input::new()
-                    .with_prompt("Symbol (leave blank to skip)")
-                    .allow_empty(true)
-                    .interact_text()
-                    .unwrap();
-
-                if !symbol.is_empty() {
-                    data.symbol = symbol;
+                if !Confirm::new()
+                    .with_prompt("Proceed with update?")
+                    .interact()
+                    .unwrap()
+                {
+                    return Ok(());
                 }
+            }
 
-                let uri: String = Input::new()
-                    .with_prompt("URI (leave blank to skip)")
-                    .allow_empty(true)
-                    .interact_text()
-                    .unwrap();
-
-                if !uri.is_empty() {
-                    data.uri = uri;
-                }
+            let report = process_update_metadata(
+                &rpc_client,
+                &config.transaction_config,
+                config.default_signer.as_ref(),
+                config.fee_payer(),
+                metadata_pubkey,
+                &metadata,
+                &update,
+            )
+            .await
+            .map(|report| {
+                report
+                    .with_account("mint", mint_pubkey)
+                    .with_account("metadata", metadata_pubkey)
+            })
+            .unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
 
-                let seller_fee_basis_points: String = Input::new()
-                    .with_prompt("Seller fee basis points (leave blank to skip)")
-                    .allow_empty(true)
-                    .interact_text()
-                    .unwrap();
+            print_report(&report, config.output_format);
+        }
+        ("metadata", arg_matches) => match arg_matches.subcommand() {
+            Some(("generate", arg_matches)) => {
+                let metadata = FungibleMetadataJson {
+                    name: arg_matches.get_one::<String>("name").unwrap().clone(),
+                    symbol: arg_matches.get_one::<String>("symbol").unwrap().clone(),
+                    description: arg_matches
+                        .get_one::<String>("description")
+                        .unwrap()
+                        .clone(),
+                    image: arg_matches.get_one::<String>("image").unwrap().clone(),
+                    extensions: arg_matches
+                        .get_many::<String>("extension")
+                        .unwrap_or_default()
+                        .filter_map(|extension| extension.split_once('='))
+                        .map(|(key, value)| (key.to_string(), value.to_string()))
+                        .collect(),
+                };
+                metadata.validate(None).unwrap_or_else(|err| {
+                    eprintln!("error: {err}");
+                    exit(1);
+                });
 
-                if !seller_fee_basis_points.is_empty() {
-                    data.seller_fee_basis_points = seller_fee_basis_points.parse::<u16>().unwrap();
+                let json = serde_json::to_string_pretty(&metadata).unwrap();
+                if let Some(path) = arg_matches.value_of("out") {
+                    std::fs::write(path, json + "\n").unwrap_or_else(|err| {
+                        eprintln!("error: unable to write {path}: {err}");
+                        exit(1);
+                    });
+                    eprintln!("Wrote metadata JSON to {path}");
+                } else {
+                    println!("{json}");
                 }
+            }
+            Some(("validate", arg_matches)) => {
+                let path = arg_matches.value_of("path").unwrap();
+                let onchain = match pubkey_of(arg_matches, "mint") {
+                    Some(mint_pubkey) => {
+                        let (_, metadata) = get_metadata(&rpc_client, &mint_pubkey)
+                            .await
+                            .unwrap_or_else(|err| {
+                                eprintln!("error: {err}");
+                                exit(1);
+                            });
+                        Some((metadata.name, metadata.symbol))
+                    }
+                    None => None,
+                };
 
-                update_metadata_builder.data(data.clone());
-
-                let is_mutable: bool = Confirm::new()
-                    .with_prompt("Is mutable? ")
-                    .interact()
-                    .unwrap();
-
-                update_metadata_builder.is_mutable(is_mutable);
+                let result = validate_metadata_file(
+                    path,
+                    onchain
+                        .as_ref()
+                        .map(|(name, symbol)| (name.as_str(), symbol.as_str())),
+                );
+                print_report(
+                    &ValidationReport::new(path, result.map(|_| ())),
+                    config.output_format,
+                );
             }
+            _ => unreachable!(),
+        },
+        ("signMetadata", arg_matches) => {
+            let mint_pubkey = pubkey_of(arg_matches, "mint_pubkey").unwrap();
 
-            let confirm: bool = Confirm::new()
-                .with_prompt("Do you want to update the update authority?")
-                .interact()
-                .unwrap();
+            let report = process_sign_metadata(
+                &rpc_client,
+                &config.transaction_config,
+                config.default_signer.as_ref(),
+                config.fee_payer(),
+                mint_pubkey,
+                true,
+            )
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
 
-            if confirm {
-                let update_authority: String = Input::new()
-                    .with_prompt("Update authority address")
-                    .interact_text()
-                    .unwrap();
+            print_report(&report, config.output_format);
+        }
+        ("removeCreatorVerification", arg_matches) => {
+            let mint_pubkey = pubkey_of(arg_matches, "mint_pubkey").unwrap();
 
-                update_metadata_builder
-                    .new_update_authority(Pubkey::from_str(&update_authority).unwrap());
-            }
+            let report = process_sign_metadata(
+                &rpc_client,
+                &config.transaction_config,
+                config.default_signer.as_ref(),
+                config.fee_payer(),
+                mint_pubkey,
+                false,
+            )
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
 
-            println!("New metadata:");
-            println!("{:#?}", data);
+            print_report(&report, config.output_format);
+        }
+        ("verifyCollection", arg_matches) => {
+            let mint_pubkey = pubkey_of(arg_matches, "mint_pubkey").unwrap();
 
-            let confirm = Confirm::new()
-                .with_prompt("Proceed with update?")
-                .interact()
-                .unwrap();
+            let report = process_verify_collection(
+                &rpc_client,
+                &config.transaction_config,
+                config.default_signer.as_ref(),
+                config.fee_payer(),
+                mint_pubkey,
+                true,
+            )
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
 
-            if confirm {
-                let signature = process_update_metadata(
-                    &rpc_client,
-                    config.default_signer.as_ref(),
-                    update_metadata_builder,
-                )
-                .await
-                .unwrap_or_else(|err| {
-                    eprintln!("error: {err}");
-                    exit(1);
-                });
+            print_report(&report, config.output_format);
+        }
+        ("unverifyCollection", arg_matches) => {
+            let mint_pubkey = pubkey_of(arg_matches, "mint_pubkey").unwrap();
 
-                println!("Signature: {signature}");
-            }
+            let report = process_verify_collection(
+                &rpc_client,
+                &config.transaction_config,
+                config.default_signer.as_ref(),
+                config.fee_payer(),
+                mint_pubkey,
+                false,
+            )
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+
+            print_report(&report, config.output_format);
         }
         ("updateAuthorities", arg_matches) => {
             let mint_pubkey = pubkey_of(arg_matches, "mint_pubkey").unwrap();
 
-            let options = vec!["Mint authority", "Freeze authority", "Owner authority"];
+            let token_program =
+                resolve_token_program(&rpc_client, config.token_program, &mint_pubkey)
+                    .await
+                    .unwrap_or_else(|err| {
+                        eprintln!("error: {err}");
+                        exit(1);
+                    });
+
+            let options = vec!["Mint authority", "Freeze authority", "Token account owner"];
 
             let ix: Instruction;
             let choice = dialoguer::Select::new()
@@ -572,21 +2265,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                             .interact_text()
                             .unwrap();
 
-                        ix = spl_token::instruction::set_authority(
-                            &spl_token::ID,
+                        ix = spl_token_2022::instruction::set_authority(
+                            &token_program,
                             &mint_pubkey,
                             Some(&Pubkey::from_str(&new_authority).unwrap()),
-                            spl_token::instruction::AuthorityType::MintTokens,
+                            spl_token_2022::instruction::AuthorityType::MintTokens,
                             &config.default_signer.pubkey(),
                             &[&config.default_signer.pubkey()],
                         )
                         .unwrap();
                     } else {
-                        ix = spl_token::instruction::set_authority(
-                            &spl_token::ID,
+                        ix = spl_token_2022::instruction::set_authority(
+                            &token_program,
                             &mint_pubkey,
                             None,
-                            spl_token::instruction::AuthorityType::MintTokens,
+                            spl_token_2022::instruction::AuthorityType::MintTokens,
                             &config.default_signer.pubkey(),
                             &[&config.default_signer.pubkey()],
                         )
@@ -606,21 +2299,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                             .interact_text()
                             .unwrap();
 
-                        ix = spl_token::instruction::set_authority(
-                            &spl_token::ID,
+                        ix = spl_token_2022::instruction::set_authority(
+                            &token_program,
                             &mint_pubkey,
                             Some(&Pubkey::from_str(&new_authority).unwrap()),
-                            spl_token::instruction::AuthorityType::FreezeAccount,
+                            spl_token_2022::instruction::AuthorityType::FreezeAccount,
                             &config.default_signer.pubkey(),
                             &[&config.default_signer.pubkey()],
                         )
                         .unwrap();
                     } else {
-                        ix = spl_token::instruction::set_authority(
-                            &spl_token::ID,
+                        ix = spl_token_2022::instruction::set_authority(
+                            &token_program,
                             &mint_pubkey,
                             None,
-                            spl_token::instruction::AuthorityType::FreezeAccount,
+                            spl_token_2022::instruction::AuthorityType::FreezeAccount,
                             &config.default_signer.pubkey(),
                             &[&config.default_signer.pubkey()],
                         )
@@ -628,50 +2321,156 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     }
                 }
                 2 => {
-                    todo!();
-                    // let new_authority: String = Input::new()
-                    //     .with_prompt("New authority owner address")
-                    //     .interact_text()
-                    //     .unwrap();
-
-                    // ix = spl_token::instruction::set_authority(
-                    //     &spl_token::ID,
-                    //     &mint_pubkey,
-                    //     Some(&Pubkey::from_str(&new_authority).unwrap()),
-                    //     spl_token::instruction::AuthorityType::AccountOwner,
-                    //     &config.default_signer.pubkey(),
-                    //     &[&config.default_signer.pubkey()],
-                    // )
-                    // .unwrap();
+                    // The associated token program makes Token-2022 accounts immutable-owner.
+                    if token_program == spl_token_2022::ID {
+                        eprintln!(
+                            "error: Token-2022 associated token accounts cannot change owner"
+                        );
+                        exit(1);
+                    }
+                    let token_account =
+                        spl_associated_token_account::get_associated_token_address_with_program_id(
+                            &config.default_signer.pubkey(),
+                            &mint_pubkey,
+                            &token_program,
+                        );
+                    let new_owner: String = Input::new()
+                        .with_prompt(format!("New owner address of {token_account}"))
+                        .validate_with(|input: &String| Pubkey::from_str(input).map(|_| ()))
+                        .interact_text()
+                        .unwrap();
+
+                    ix = spl_token_2022::instruction::set_authority(
+                        &token_program,
+                        &token_account,
+                        Some(&Pubkey::from_str(&new_owner).unwrap()),
+                        spl_token_2022::instruction::AuthorityType::AccountOwner,
+                        &config.default_signer.pubkey(),
+                        &[&config.default_signer.pubkey()],
+                    )
+                    .unwrap();
                 }
                 _ => unreachable!(),
             }
 
-            let signature =
-                process_update_authorities(&rpc_client, config.default_signer.as_ref(), ix)
-                    .await
-                    .unwrap_or_else(|err| {
-                        eprintln!("error: {err}");
-                        exit(1);
-                    });
+            let report = process_update_authorities(
+                &rpc_client,
+                &config.transaction_config,
+                config.default_signer.as_ref(),
+                config.fee_payer(),
+                ix,
+            )
+            .await
+            .map(|report| report.with_account("mint", mint_pubkey))
+            .unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
 
-            println!("Signature: {signature}");
+            print_report(&report, config.output_format);
         }
         ("addToLiquidity", arg_matches) => {
-            let amount = arg_matches.get_one::<String>("amount").unwrap();
+            let amount = amount_of(arg_matches).unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
             let mint_of_token_sent = pubkey_of(arg_matches, "mint_of_token_sent").unwrap();
             let program_id = if let Some(program_id) = arg_matches.get_one::<String>("program_id") {
-                Pubkey::from_str(&program_id).unwrap()
+                Pubkey::from_str(program_id).unwrap()
             } else {
                 bridge_program::ID
             };
 
-            let signature = process_add_liquidity(
+            let report = process_add_liquidity(
                 &rpc_client,
+                &config.transaction_config,
                 config.default_signer.as_ref(),
+                config.fee_payer(),
                 program_id,
-                amount.parse::<u64>().unwrap(),
+                &amount,
                 mint_of_token_sent,
+                config.token_program,
+                arg_matches.get_one::<u8>("mint_decimals").copied(),
+                &annotation_of(arg_matches),
+            )
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+
+            print_report(&report, config.output_format);
+        }
+        ("createNonceAccount", arg_matches) => {
+            let nonce_account = signer_from_path(
+                arg_matches,
+                arg_matches.value_of("nonce_account_keypair").unwrap(),
+                "nonce_account_keypair",
+                &mut wallet_manager,
+            )
+            .unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+
+            let report = nonce_account::process_create_nonce_account(
+                &rpc_client,
+                &config.transaction_config,
+                config.default_signer.as_ref(),
+                config.fee_payer(),
+                nonce_account.as_ref(),
+                lamports_of_sol(arg_matches, "amount"),
+            )
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+
+            print_report(&report, config.output_format);
+        }
+        ("showNonceAccount", arg_matches) => {
+            let nonce_pubkey = pubkey_of(arg_matches, "nonce_account").unwrap();
+
+            let nonce_account = nonce_account::get_nonce_account(&rpc_client, &nonce_pubkey)
+                .await
+                .unwrap_or_else(|err| {
+                    eprintln!("error: {err}");
+                    exit(1);
+                });
+
+            config.output_format.print(&nonce_account);
+        }
+        ("advanceNonceAccount", arg_matches) => {
+            let nonce_pubkey = pubkey_of(arg_matches, "nonce_account").unwrap();
+
+            let report = nonce_account::process_advance_nonce_account(
+                &rpc_client,
+                &config.transaction_config,
+                config.default_signer.as_ref(),
+                config.fee_payer(),
+                nonce_pubkey,
+            )
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("error: {err}");
+                exit(1);
+            });
+
+            print_report(&report, config.output_format);
+        }
+        ("withdrawFromNonceAccount", arg_matches) => {
+            let nonce_pubkey = pubkey_of(arg_matches, "nonce_account").unwrap();
+            let destination = pubkey_of(arg_matches, "destination").unwrap();
+
+            let report = nonce_account::process_withdraw_from_nonce_account(
+                &rpc_client,
+                &config.transaction_config,
+                config.default_signer.as_ref(),
+                config.fee_payer(),
+                nonce_pubkey,
+                destination,
+                lamports_of_sol(arg_matches, "amount").unwrap(),
             )
             .await
             .unwrap_or_else(|err| {
@@ -679,7 +2478,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 exit(1);
             });
 
-            println!("Signature: {signature}");
+            print_report(&report, config.output_format);
         }
 
         _ => unreachable!(),
@@ -0,0 +1,99 @@
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+
+/// Memo and reference keys identifying a transaction for bookkeeping.
+#[derive(Debug, Default, Clone)]
+pub struct Annotation {
+    pub memo: Option<String>,
+    pub references: Vec<Pubkey>,
+}
+
+impl Annotation {
+    /// Appends the memo signed by `authority`. References are added as read-only accounts of
+    /// the memo instruction, so that `getSignaturesForAddress` finds the transaction, and an
+    /// empty memo carries them when there is no memo text.
+    ///
+    /// Current versions of the memo program reject accounts that are not signers, so a memo
+    /// with references goes to Memo v1, which ignores its accounts.
+    pub fn apply(&self, instructions: &mut Vec<Instruction>, authority: &Pubkey) {
+        let memo = match (&self.memo, self.references.is_empty()) {
+            (Some(memo), true) => {
+                instructions.push(spl_memo::build_memo(memo.as_bytes(), &[authority]));
+                return;
+            }
+            (None, true) => return,
+            (memo, false) => memo.as_deref().unwrap_or_default(),
+        };
+
+        let accounts = std::iter::once(AccountMeta::new_readonly(*authority, true))
+            .chain(
+                self.references
+                    .iter()
+                    .map(|reference| AccountMeta::new_readonly(*reference, false)),
+            )
+            .collect();
+        instructions.push(Instruction {
+            program_id: spl_memo::v1::id(),
+            accounts,
+            data: memo.as_bytes().to_vec(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer() -> Instruction {
+        Instruction::new_with_bytes(
+            spl_token::ID,
+            &[3],
+            vec![
+                AccountMeta::new(Pubkey::new_unique(), false),
+                AccountMeta::new_readonly(Pubkey::new_unique(), true),
+            ],
+        )
+    }
+
+    #[test]
+    fn references_go_on_an_empty_memo_and_leave_the_transfer_unchanged() {
+        let (authority, reference) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let transfer = transfer();
+        let mut instructions = vec![transfer.clone()];
+        let annotation = Annotation {
+            memo: None,
+            references: vec![reference],
+        };
+
+        annotation.apply(&mut instructions, &authority);
+
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[0], transfer);
+        assert_eq!(instructions[1].program_id, spl_memo::v1::id());
+        assert!(instructions[1].data.is_empty());
+        assert_eq!(
+            instructions[1].accounts,
+            vec![
+                AccountMeta::new_readonly(authority, true),
+                AccountMeta::new_readonly(reference, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn memo_without_references_is_signed_by_the_authority() {
+        let authority = Pubkey::new_unique();
+        let mut instructions = vec![transfer()];
+        let annotation = Annotation {
+            memo: Some("Q3 payroll".to_string()),
+            references: Vec::new(),
+        };
+
+        annotation.apply(&mut instructions, &authority);
+
+        assert_eq!(
+            instructions[1],
+            spl_memo::build_memo(b"Q3 payroll", &[&authority])
+        );
+    }
+}
@@ -0,0 +1,14 @@
+use mpl_token_metadata::accounts::Metadata;
+use mpl_token_metadata::types::ProgrammableConfig;
+use solana_sdk::pubkey;
+use solana_sdk::pubkey::Pubkey;
+
+pub const MPL_TOKEN_AUTH_RULES_ID: Pubkey = pubkey!("auth9SigNpDKz4sJJ1DfCTuZrZNSAgh9sFD3rboVmgg");
+
+/// Returns the rule set of a programmable NFT.
+pub fn rule_set_of(metadata: &Metadata) -> Option<Pubkey> {
+    match metadata.programmable_config {
+        Some(ProgrammableConfig::V1 { rule_set }) => rule_set,
+        None => None,
+    }
+}
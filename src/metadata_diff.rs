@@ -0,0 +1,183 @@
+use console::style;
+use mpl_token_metadata::accounts::Metadata;
+use mpl_token_metadata::types::{Collection, Creator, DataV2, TokenStandard, UseMethod, Uses};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::metadata::rule_set_of;
+use crate::update_metadata::MetadataUpdate;
+
+/// Metadata strings are padded with NUL bytes up to their maximum length on-chain.
+pub fn trim_padding(value: &str) -> &str {
+    value.trim_end_matches('\0')
+}
+
+fn format_creators(creators: &Option<Vec<Creator>>) -> String {
+    match creators {
+        Some(creators) if !creators.is_empty() => creators
+            .iter()
+            .map(|creator| {
+                let verified = if creator.verified { ", verified" } else { "" };
+                format!("{} ({}%{verified})", creator.address, creator.share)
+            })
+            .collect::<Vec<_>>()
+            .join(", "),
+        _ => "none".to_string(),
+    }
+}
+
+fn format_collection(collection: &Option<Collection>) -> String {
+    match collection {
+        Some(collection) if collection.verified => format!("{} (verified)", collection.key),
+        Some(collection) => collection.key.to_string(),
+        None => "none".to_string(),
+    }
+}
+
+fn format_uses(uses: &Option<Uses>) -> String {
+    match uses {
+        Some(uses) => {
+            let method = match uses.use_method {
+                UseMethod::Burn => "burn",
+                UseMethod::Multiple => "multiple",
+                UseMethod::Single => "single",
+            };
+            format!("{method}, {} of {} remaining", uses.remaining, uses.total)
+        }
+        None => "none".to_string(),
+    }
+}
+
+fn format_token_standard(token_standard: &Option<TokenStandard>) -> String {
+    match token_standard {
+        Some(TokenStandard::Fungible) => "fungible",
+        Some(TokenStandard::FungibleAsset) => "fungible_asset",
+        Some(TokenStandard::NonFungible) => "non_fungible",
+        Some(TokenStandard::NonFungibleEdition) => "non_fungible_edition",
+        Some(TokenStandard::ProgrammableNonFungible) => "programmable_non_fungible",
+        Some(TokenStandard::ProgrammableNonFungibleEdition) => "programmable_non_fungible_edition",
+        None => "none",
+    }
+    .to_string()
+}
+
+fn format_option(value: Option<Pubkey>) -> String {
+    value.map_or_else(|| "none".to_string(), |value| value.to_string())
+}
+
+/// Field names and values, in the order they are shown, after `update` if one is given.
+fn field_values(
+    metadata: &Metadata,
+    update: Option<&MetadataUpdate>,
+) -> Vec<(&'static str, String)> {
+    let current = data_of(metadata);
+    let data = update
+        .and_then(|update| update.data.as_ref())
+        .unwrap_or(&current);
+    let is_mutable = update
+        .and_then(|update| update.is_mutable)
+        .unwrap_or(metadata.is_mutable);
+    let update_authority = update
+        .and_then(|update| update.new_update_authority)
+        .unwrap_or(metadata.update_authority);
+    let primary_sale_happened = update
+        .and_then(|update| update.primary_sale_happened)
+        .unwrap_or(metadata.primary_sale_happened);
+    let token_standard = update
+        .and_then(|update| update.token_standard)
+        .or(metadata.token_standard);
+    let rule_set = update
+        .and_then(|update| update.rule_set)
+        .unwrap_or_else(|| rule_set_of(metadata));
+
+    vec![
+        ("Name", format!("{:?}", trim_padding(&data.name))),
+        ("Symbol", format!("{:?}", trim_padding(&data.symbol))),
+        ("URI", format!("{:?}", trim_padding(&data.uri))),
+        (
+            "Seller fee",
+            format!("{} basis points", data.seller_fee_basis_points),
+        ),
+        ("Creators", format_creators(&data.creators)),
+        ("Collection", format_collection(&data.collection)),
+        ("Uses", format_uses(&data.uses)),
+        ("Mutable", is_mutable.to_string()),
+        ("Update authority", update_authority.to_string()),
+        ("Primary sale happened", primary_sale_happened.to_string()),
+        ("Token standard", format_token_standard(&token_standard)),
+        ("Rule set", format_option(rule_set)),
+    ]
+}
+
+pub fn data_of(metadata: &Metadata) -> DataV2 {
+    DataV2 {
+        name: metadata.name.clone(),
+        symbol: metadata.symbol.clone(),
+        uri: metadata.uri.clone(),
+        seller_fee_basis_points: metadata.seller_fee_basis_points,
+        creators: metadata.creators.clone(),
+        collection: metadata.collection.clone(),
+        uses: metadata.uses.clone(),
+    }
+}
+
+/// Prints the current metadata fields to stderr.
+pub fn print_metadata(metadata: &Metadata) {
+    for (name, value) in field_values(metadata, None) {
+        eprintln!("  {name}: {value}");
+    }
+}
+
+/// Prints each field `update` changes as `old → new` to stderr, followed by warnings for
+/// changes that cannot be undone.
+pub fn print_metadata_diff(metadata: &Metadata, update: &MetadataUpdate) {
+    let old = field_values(metadata, None);
+    let new = field_values(metadata, Some(update));
+
+    let changes: Vec<_> = old
+        .into_iter()
+        .zip(new)
+        .filter(|((_, old), (_, new))| old != new)
+        .collect();
+    if changes.is_empty() {
+        eprintln!("No metadata changes");
+        return;
+    }
+
+    eprintln!("Metadata changes:");
+    for ((name, old), (_, new)) in changes {
+        eprintln!(
+            "  {name}: {} → {}",
+            style(old).for_stderr().red(),
+            style(new).for_stderr().green()
+        );
+    }
+
+    if metadata.is_mutable && update.is_mutable == Some(false) {
+        eprintln!(
+            "{}",
+            style("warning: the metadata becomes immutable, this cannot be undone")
+                .for_stderr()
+                .yellow()
+        );
+    }
+    if !metadata.primary_sale_happened && update.primary_sale_happened == Some(true) {
+        eprintln!(
+            "{}",
+            style("warning: the primary sale is marked as happened, this cannot be undone")
+                .for_stderr()
+                .yellow()
+        );
+    }
+    if let Some(new_update_authority) = update.new_update_authority {
+        if new_update_authority != metadata.update_authority {
+            eprintln!(
+                "{}",
+                style(format!(
+                    "warning: only {new_update_authority} can update the metadata afterwards"
+                ))
+                .for_stderr()
+                .yellow()
+            );
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use mpl_token_metadata::{MAX_NAME_LENGTH, MAX_SYMBOL_LENGTH, MAX_URI_LENGTH};
+use serde::{Deserialize, Serialize};
+
+use crate::metadata_diff::trim_padding;
+use crate::output::Report;
+
+/// Off-chain JSON of a fungible token under the Metaplex token standard.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FungibleMetadataJson {
+    pub name: String,
+    pub symbol: String,
+    #[serde(default)]
+    pub description: String,
+    pub image: String,
+    /// Links such as `website` or `twitter`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extensions: BTreeMap<String, String>,
+}
+
+/// Checks the byte limits Token Metadata enforces on the on-chain name, symbol and URI.
+pub fn check_onchain_fields(name: &str, symbol: &str, uri: &str) -> Result<(), String> {
+    let mut problems = Vec::new();
+    for (field, value, limit) in [
+        ("name", name, MAX_NAME_LENGTH),
+        ("symbol", symbol, MAX_SYMBOL_LENGTH),
+        ("uri", uri, MAX_URI_LENGTH),
+    ] {
+        let len = trim_padding(value).len();
+        if len > limit {
+            problems.push(format!(
+                "{field} is {len} bytes, at most {limit} are allowed"
+            ));
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems.join("; "))
+    }
+}
+
+impl FungibleMetadataJson {
+    /// Checks the JSON against the standard and, when given, the on-chain name and symbol it
+    /// is published for.
+    pub fn validate(&self, onchain: Option<(&str, &str)>) -> Result<(), String> {
+        let mut problems = Vec::new();
+        if self.name.is_empty() {
+            problems.push("name is empty".to_string());
+        }
+        if self.symbol.is_empty() {
+            problems.push("symbol is empty".to_string());
+        }
+        if self.image.is_empty() {
+            problems.push("image is empty".to_string());
+        }
+        if let Err(err) = check_onchain_fields(&self.name, &self.symbol, "") {
+            problems.push(err);
+        }
+        if let Some((name, symbol)) = onchain {
+            let (name, symbol) = (trim_padding(name), trim_padding(symbol));
+            if self.name != name {
+                problems.push(format!(
+                    "name {:?} does not match the on-chain name {name:?}",
+                    self.name
+                ));
+            }
+            if self.symbol != symbol {
+                problems.push(format!(
+                    "symbol {:?} does not match the on-chain symbol {symbol:?}",
+                    self.symbol
+                ));
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(problems.join("; "))
+        }
+    }
+}
+
+/// Reads a metadata JSON file and validates it against `onchain` name and symbol.
+pub fn validate_metadata_file(
+    path: &str,
+    onchain: Option<(&str, &str)>,
+) -> Result<FungibleMetadataJson, Box<dyn std::error::Error>> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|err| format!("error: unable to read {path}: {err}"))?;
+    let metadata: FungibleMetadataJson = serde_json::from_str(&contents)
+        .map_err(|err| format!("error: invalid metadata JSON in {path}: {err}"))?;
+    metadata
+        .validate(onchain)
+        .map_err(|err| format!("error: {path}: {err}"))?;
+    Ok(metadata)
+}
+
+/// Result of `metadata validate`.
+#[derive(Debug, Serialize)]
+pub struct ValidationReport {
+    pub path: String,
+    pub valid: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ValidationReport {
+    pub fn new(path: &str, result: Result<(), Box<dyn std::error::Error>>) -> Self {
+        let error = result.err().map(|err| {
+            let err = err.to_string();
+            err.strip_prefix("error: ")
+                .map(str::to_string)
+                .unwrap_or(err)
+        });
+        ValidationReport {
+            path: path.to_string(),
+            valid: error.is_none(),
+            error,
+        }
+    }
+}
+
+impl Report for ValidationReport {
+    fn is_success(&self) -> bool {
+        self.valid
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.error {
+            Some(error) => write!(f, "{error}"),
+            None => write!(f, "{} is valid", self.path),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata_json() -> FungibleMetadataJson {
+        FungibleMetadataJson {
+            name: "Bridged Token".to_string(),
+            symbol: "BRT".to_string(),
+            description: String::new(),
+            image: "https://example.com/token.png".to_string(),
+            extensions: BTreeMap::new(),
+        }
+    }
+
+    #[test]
+    fn checks_byte_limits() {
+        let name = "n".repeat(MAX_NAME_LENGTH);
+        let symbol = "s".repeat(MAX_SYMBOL_LENGTH);
+        let uri = "u".repeat(MAX_URI_LENGTH);
+        assert_eq!(check_onchain_fields(&name, &symbol, &uri), Ok(()));
+
+        let err = check_onchain_fields(&format!("{name}n"), &format!("{symbol}s"), "").unwrap_err();
+        assert!(err.starts_with("name is 33 bytes"), "{err}");
+        assert!(err.contains("symbol is 11 bytes"), "{err}");
+
+        // Six two-byte characters fit in ten characters but not in ten bytes.
+        assert!(check_onchain_fields("", "éééééé", "").is_err());
+    }
+
+    #[test]
+    fn trims_on_chain_padding_before_checking_limits() {
+        let symbol = format!("BRT{}", "\0".repeat(MAX_SYMBOL_LENGTH));
+        assert_eq!(check_onchain_fields("", &symbol, ""), Ok(()));
+    }
+
+    #[test]
+    fn compares_with_the_padded_on_chain_values() {
+        let name = format!("Bridged Token{}", "\0".repeat(19));
+        let symbol = format!("BRT{}", "\0".repeat(7));
+        assert_eq!(metadata_json().validate(Some((&name, &symbol))), Ok(()));
+
+        let err = metadata_json()
+            .validate(Some(("Other Token", "OTH")))
+            .unwrap_err();
+        assert!(err.contains("on-chain name"), "{err}");
+        assert!(err.contains("on-chain symbol"), "{err}");
+    }
+}
@@ -1,58 +1,156 @@
 use mpl_token_metadata::accounts::Metadata;
 use mpl_token_metadata::instructions::MintV1Builder;
 use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_client::rpc_config::RpcSendTransactionConfig;
-use solana_sdk::message::Message;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::program_error::ProgramError;
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::transaction::Transaction;
-use solana_sdk::{signature::Signature, signer::Signer};
+use solana_sdk::signer::Signer;
 use solana_sdk::{system_program, sysvar};
-use spl_associated_token_account::get_associated_token_address;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
 
+use crate::amount::{resolve_mint_decimals, Amount};
+use crate::memo::Annotation;
+use crate::token_program::{resolve_token_program, TokenProgram};
+use crate::transaction::{execute_transaction, TransactionConfig, TransactionReport};
+
+/// Returns whether `mint` has a Token Metadata account to mint through.
+///
+/// Token-2022 mints that keep their metadata in the mint have none, so they are minted to
+/// directly through the token program.
+pub async fn has_metadata_account(
+    rpc_client: &RpcClient,
+    token_program: &Pubkey,
+    mint: &Pubkey,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    if *token_program == spl_token::ID {
+        return Ok(true);
+    }
+
+    let (metadata, _) = Metadata::find_pda(mint);
+    Ok(rpc_client
+        .get_account_with_commitment(&metadata, rpc_client.commitment())
+        .await
+        .map_err(|err| format!("error: unable to get metadata account {metadata}: {err}"))?
+        .value
+        .is_some())
+}
+
+/// Returns `metadata_account` when given, otherwise whether `mint` has a Token Metadata
+/// account. Only Token-2022 mints need the lookup, and `sign_only` requires the answer for
+/// them so the transaction can be built without RPC access.
+pub async fn resolve_metadata_account(
+    rpc_client: &RpcClient,
+    metadata_account: Option<bool>,
+    sign_only: bool,
+    token_program: &Pubkey,
+    mint: &Pubkey,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    match metadata_account {
+        Some(metadata_account) => Ok(metadata_account),
+        None if sign_only && *token_program != spl_token::ID => Err(
+            "error: --sign-only requires --with-metadata or --no-metadata for Token-2022 mints"
+                .into(),
+        ),
+        None => has_metadata_account(rpc_client, token_program, mint).await,
+    }
+}
+
+/// Instructions minting `amount` base units to the associated token account of `receiver`,
+/// creating it if needed.
+pub fn mint_to_instructions(
+    signer: &Pubkey,
+    fee_payer: &Pubkey,
+    mint: &Pubkey,
+    receiver: &Pubkey,
+    amount: u64,
+    token_program: &Pubkey,
+    has_metadata_account: bool,
+) -> Result<Vec<Instruction>, ProgramError> {
+    let receiver_ata = get_associated_token_address_with_program_id(receiver, mint, token_program);
+
+    if has_metadata_account {
+        let (metadata, _) = Metadata::find_pda(mint);
+
+        Ok(vec![MintV1Builder::new()
+            .token(receiver_ata)
+            .token_owner(Some(*receiver))
+            .metadata(metadata)
+            .mint(*mint)
+            .amount(amount)
+            .authority(*signer)
+            .payer(*fee_payer)
+            .system_program(system_program::ID)
+            .sysvar_instructions(sysvar::instructions::ID)
+            .spl_token_program(*token_program)
+            .spl_ata_program(spl_associated_token_account::ID)
+            .instruction()])
+    } else {
+        Ok(vec![
+            create_associated_token_account_idempotent(fee_payer, receiver, mint, token_program),
+            spl_token_2022::instruction::mint_to(
+                token_program,
+                mint,
+                &receiver_ata,
+                signer,
+                &[],
+                amount,
+            )?,
+        ])
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub async fn process_mint_to(
     rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
     signer: &dyn Signer,
+    fee_payer: &dyn Signer,
     mint_pubkey: Pubkey,
     receiver_pubkey: Pubkey,
-    amount: u64,
-) -> Result<Signature, Box<dyn std::error::Error>> {
-    let receiver_ata = get_associated_token_address(&receiver_pubkey, &mint_pubkey);
-
-    let (metadata, _) = Metadata::find_pda(&mint_pubkey);
-
-    let mint_to_ix = MintV1Builder::new()
-        .token(receiver_ata)
-        .token_owner(Option::<Pubkey>::Some(receiver_pubkey))
-        .metadata(metadata)
-        .mint(mint_pubkey)
-        .amount(amount)
-        .authority(signer.pubkey())
-        .payer(signer.pubkey())
-        .system_program(system_program::ID)
-        .sysvar_instructions(sysvar::instructions::ID)
-        .spl_token_program(spl_token::ID)
-        .spl_ata_program(spl_associated_token_account::ID)
-        .instruction();
-
-    let mut tx = Transaction::new_unsigned(Message::new(&[mint_to_ix], Some(&signer.pubkey())));
-
-    let blockhash = rpc_client
-        .get_latest_blockhash()
-        .await
-        .map_err(|err| format!("error: unable to get latest blockhash: {err}"))?;
+    amount: &Amount,
+    token_program: Option<TokenProgram>,
+    mint_decimals: Option<u8>,
+    metadata_account: Option<bool>,
+    annotation: &Annotation,
+) -> Result<TransactionReport, Box<dyn std::error::Error>> {
+    let token_program = resolve_token_program(rpc_client, token_program, &mint_pubkey).await?;
+    let decimals =
+        resolve_mint_decimals(rpc_client, mint_decimals, tx_config.sign_only, &mint_pubkey).await?;
+    let metadata_account = resolve_metadata_account(
+        rpc_client,
+        metadata_account,
+        tx_config.sign_only,
+        &token_program,
+        &mint_pubkey,
+    )
+    .await?;
+    let amount = amount
+        .to_token_amount(decimals)
+        .map_err(|err| format!("error: {err}"))?;
+    let receiver_ata = get_associated_token_address_with_program_id(
+        &receiver_pubkey,
+        &mint_pubkey,
+        &token_program,
+    );
 
-    tx.try_sign(&[signer], blockhash)
-        .map_err(|err| format!("error: failed to sign transaction: {err}"))?;
+    let mut mint_to_ixs = mint_to_instructions(
+        &signer.pubkey(),
+        &fee_payer.pubkey(),
+        &mint_pubkey,
+        &receiver_pubkey,
+        amount.amount,
+        &token_program,
+        metadata_account,
+    )?;
+    annotation.apply(&mut mint_to_ixs, &signer.pubkey());
 
-    let config = RpcSendTransactionConfig {
-        skip_preflight: true,
-        ..RpcSendTransactionConfig::default()
-    };
-
-    let signature = rpc_client
-        .send_transaction_with_config(&tx, config)
+    execute_transaction(rpc_client, tx_config, &mint_to_ixs, fee_payer, &[signer])
         .await
-        .map_err(|err| format!("error: send transaction: {err}"))?;
-
-    Ok(signature)
+        .map(|report| {
+            report
+                .with_account("mint", mint_pubkey)
+                .with_account("tokenAccount", receiver_ata)
+                .with_amount(amount)
+        })
 }
@@ -0,0 +1,148 @@
+use std::fmt;
+
+use serde::{Serialize, Serializer};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::nonce_utils::nonblocking::{data_from_account, get_account_with_commitment};
+use solana_sdk::native_token::lamports_to_sol;
+use solana_sdk::nonce::state::Data;
+use solana_sdk::nonce::State;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+use solana_sdk::system_instruction;
+
+use crate::transaction::{execute_transaction, TransactionConfig, TransactionReport};
+
+pub struct NonceAccountInfo {
+    pub pubkey: Pubkey,
+    pub balance: u64,
+    pub data: Data,
+}
+
+impl fmt::Display for NonceAccountInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Nonce account: {}", self.pubkey)?;
+        writeln!(f, "Balance: {} SOL", lamports_to_sol(self.balance))?;
+        writeln!(f, "Nonce blockhash: {}", self.data.blockhash())?;
+        writeln!(
+            f,
+            "Fee: {} lamports per signature",
+            self.data.get_lamports_per_signature()
+        )?;
+        write!(f, "Authority: {}", self.data.authority)
+    }
+}
+
+impl Serialize for NonceAccountInfo {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Info {
+            pubkey: String,
+            balance: u64,
+            blockhash: String,
+            lamports_per_signature: u64,
+            authority: String,
+        }
+
+        Info {
+            pubkey: self.pubkey.to_string(),
+            balance: self.balance,
+            blockhash: self.data.blockhash().to_string(),
+            lamports_per_signature: self.data.get_lamports_per_signature(),
+            authority: self.data.authority.to_string(),
+        }
+        .serialize(serializer)
+    }
+}
+
+pub async fn get_nonce_account(
+    rpc_client: &RpcClient,
+    nonce_pubkey: &Pubkey,
+) -> Result<NonceAccountInfo, Box<dyn std::error::Error>> {
+    let account = get_account_with_commitment(rpc_client, nonce_pubkey, rpc_client.commitment())
+        .await
+        .map_err(|err| format!("error: unable to get nonce account {nonce_pubkey}: {err}"))?;
+
+    let data = data_from_account(&account)
+        .map_err(|err| format!("error: invalid nonce account {nonce_pubkey}: {err}"))?;
+
+    Ok(NonceAccountInfo {
+        pubkey: *nonce_pubkey,
+        balance: account.lamports,
+        data,
+    })
+}
+
+pub async fn process_create_nonce_account(
+    rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
+    signer: &dyn Signer,
+    fee_payer: &dyn Signer,
+    nonce_account: &dyn Signer,
+    lamports: Option<u64>,
+) -> Result<TransactionReport, Box<dyn std::error::Error>> {
+    let lamports = match lamports {
+        Some(lamports) => lamports,
+        None => rpc_client
+            .get_minimum_balance_for_rent_exemption(State::size())
+            .await
+            .map_err(|err| format!("error: unable to get rent exemption: {err}"))?,
+    };
+
+    let create_ixs = system_instruction::create_nonce_account(
+        &fee_payer.pubkey(),
+        &nonce_account.pubkey(),
+        &signer.pubkey(),
+        lamports,
+    );
+
+    execute_transaction(
+        rpc_client,
+        tx_config,
+        &create_ixs,
+        fee_payer,
+        &[signer, nonce_account],
+    )
+    .await
+    .map(|report| report.with_account("nonceAccount", nonce_account.pubkey()))
+}
+
+pub async fn process_advance_nonce_account(
+    rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
+    signer: &dyn Signer,
+    fee_payer: &dyn Signer,
+    nonce_pubkey: Pubkey,
+) -> Result<TransactionReport, Box<dyn std::error::Error>> {
+    let advance_ix = system_instruction::advance_nonce_account(&nonce_pubkey, &signer.pubkey());
+
+    execute_transaction(rpc_client, tx_config, &[advance_ix], fee_payer, &[signer])
+        .await
+        .map(|report| report.with_account("nonceAccount", nonce_pubkey))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn process_withdraw_from_nonce_account(
+    rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
+    signer: &dyn Signer,
+    fee_payer: &dyn Signer,
+    nonce_pubkey: Pubkey,
+    destination: Pubkey,
+    lamports: u64,
+) -> Result<TransactionReport, Box<dyn std::error::Error>> {
+    let withdraw_ix = system_instruction::withdraw_nonce_account(
+        &nonce_pubkey,
+        &signer.pubkey(),
+        &destination,
+        lamports,
+    );
+
+    execute_transaction(rpc_client, tx_config, &[withdraw_ix], fee_payer, &[signer])
+        .await
+        .map(|report| {
+            report
+                .with_account("nonceAccount", nonce_pubkey)
+                .with_account("destination", destination)
+        })
+}
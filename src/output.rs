@@ -0,0 +1,95 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::{Serialize, Serializer};
+use solana_sdk::pubkey::Pubkey;
+
+/// Result of a command, printed in the selected output format.
+pub trait Report: Serialize + Display {
+    /// Whether the command succeeded. The CLI exits with an error status otherwise.
+    fn is_success(&self) -> bool;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Display,
+    Json,
+    JsonCompact,
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "display" => Ok(OutputFormat::Display),
+            "json" => Ok(OutputFormat::Json),
+            "json-compact" => Ok(OutputFormat::JsonCompact),
+            _ => Err(format!(
+                "expected `display`, `json` or `json-compact`, got `{s}`"
+            )),
+        }
+    }
+}
+
+impl OutputFormat {
+    /// Prints a command result to stdout in this format.
+    pub fn print<T: Serialize + Display>(&self, value: &T) {
+        match self {
+            OutputFormat::Display => println!("{value}"),
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(value).unwrap()),
+            OutputFormat::JsonCompact => println!("{}", serde_json::to_string(value).unwrap()),
+        }
+    }
+
+    /// Prints several results, separated by blank lines or as a JSON array.
+    pub fn print_all<T: Serialize + Display>(&self, values: &[T]) {
+        match self {
+            OutputFormat::Display => {
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        println!();
+                    }
+                    println!("{value}");
+                }
+            }
+            OutputFormat::Json => println!("{}", serde_json::to_string_pretty(values).unwrap()),
+            OutputFormat::JsonCompact => println!("{}", serde_json::to_string(values).unwrap()),
+        }
+    }
+}
+
+/// Serializes a value through its `Display` impl, so keys, signatures and hashes come out as
+/// base58 strings rather than byte arrays.
+pub fn display<T: Display, S: Serializer>(value: &T, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.collect_str(value)
+}
+
+pub fn display_option<T: Display, S: Serializer>(
+    value: &Option<T>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match value {
+        Some(value) => serializer.collect_str(value),
+        None => serializer.serialize_none(),
+    }
+}
+
+pub fn display_seq<T: Display, S: Serializer>(
+    values: &[T],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(values.iter().map(ToString::to_string))
+}
+
+/// Serializes named accounts as a `name: pubkey` map.
+pub fn account_map<S: Serializer>(
+    accounts: &[(&str, Pubkey)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(
+        accounts
+            .iter()
+            .map(|(name, pubkey)| (name, pubkey.to_string())),
+    )
+}
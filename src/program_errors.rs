@@ -0,0 +1,101 @@
+use mpl_token_metadata::errors::MplTokenMetadataError;
+use num_traits::FromPrimitive;
+use solana_sdk::instruction::InstructionError;
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::transaction::TransactionError;
+use spl_token::error::TokenError;
+use spl_token_2022::error::TokenError as Token2022Error;
+
+/// Turns the custom error code carried by `err` into a readable message, based on the
+/// program that failed. A program failing in a cross-program invocation fails its callers
+/// too, so the innermost program that logged the failure is used, falling back to the
+/// program of the top-level instruction.
+///
+/// SPL Token, Token-2022 and Token Metadata codes are mapped to their error enums. Anchor programs such
+/// as the bridge log their errors, so for any other program the `AnchorError` log line is used.
+pub fn decode_program_error(
+    message: &Message,
+    err: &TransactionError,
+    logs: &[String],
+) -> Option<String> {
+    let TransactionError::InstructionError(index, InstructionError::Custom(code)) = err else {
+        return None;
+    };
+
+    let program_id = match failed_program(logs) {
+        Some(program_id) => program_id,
+        None => *message
+            .instructions
+            .get(*index as usize)
+            .and_then(|ix| message.account_keys.get(ix.program_id_index as usize))?,
+    };
+
+    if program_id == spl_token::ID {
+        TokenError::from_u32(*code).map(|err| format!("SPL Token error {code:#x}: {err}"))
+    } else if program_id == spl_token_2022::ID {
+        Token2022Error::from_u32(*code).map(|err| format!("Token-2022 error {code:#x}: {err}"))
+    } else if program_id == mpl_token_metadata::ID {
+        MplTokenMetadataError::from_u32(*code).map(|err| {
+            let description = err.to_string();
+            if description.is_empty() {
+                format!("Token Metadata error {code:#x}: {err:?}")
+            } else {
+                format!("Token Metadata error {code:#x}: {err:?} ({description})")
+            }
+        })
+    } else {
+        logs.iter()
+            .find_map(|log| log.strip_prefix("Program log: AnchorError"))
+            .map(|log| format!("AnchorError{log}"))
+    }
+}
+
+/// Returns the program of the first `Program <id> failed: custom program error` log line.
+/// The runtime logs the failure of the innermost invocation first, then of each caller.
+fn failed_program(logs: &[String]) -> Option<Pubkey> {
+    logs.iter().find_map(|log| {
+        let (program_id, _) = log
+            .strip_prefix("Program ")?
+            .split_once(" failed: custom program error: ")?;
+        program_id.parse().ok()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use solana_sdk::instruction::{AccountMeta, Instruction};
+
+    use super::*;
+
+    #[test]
+    fn decodes_the_error_of_the_innermost_failing_program() {
+        let bridge = Pubkey::new_unique();
+        let message = Message::new(
+            &[Instruction::new_with_bytes(
+                bridge,
+                &[],
+                vec![AccountMeta::new(Pubkey::new_unique(), true)],
+            )],
+            None,
+        );
+        let err = TransactionError::InstructionError(0, InstructionError::Custom(1));
+        let logs = [
+            format!("Program {bridge} invoke [1]"),
+            format!("Program {} invoke [2]", spl_token::ID),
+            "Program log: Error: insufficient funds".to_string(),
+            format!(
+                "Program {} failed: custom program error: 0x1",
+                spl_token::ID
+            ),
+            format!("Program {bridge} failed: custom program error: 0x1"),
+        ];
+
+        assert_eq!(
+            decode_program_error(&message, &err, &logs).as_deref(),
+            Some("SPL Token error 0x1: Insufficient funds")
+        );
+        // Without the logs, the top-level program is all there is to go by.
+        assert_eq!(decode_program_error(&message, &err, &[]), None);
+    }
+}
@@ -0,0 +1,76 @@
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::program_option::COption;
+use solana_sdk::pubkey::Pubkey;
+use spl_token_2022::extension::permanent_delegate::get_permanent_delegate;
+use spl_token_2022::extension::StateWithExtensions;
+use spl_token_2022::state::{Account, AccountState, Mint};
+
+use crate::amount::TokenAmount;
+
+/// Returns the supply of `mint`, along with its permanent delegate if it has one.
+pub async fn get_mint_supply(
+    rpc_client: &RpcClient,
+    mint: &Pubkey,
+) -> Result<(TokenAmount, Option<Pubkey>), Box<dyn std::error::Error>> {
+    let data = rpc_client
+        .get_account_data(mint)
+        .await
+        .map_err(|err| format!("error: unable to get mint {mint}: {err}"))?;
+
+    let mint_state = StateWithExtensions::<Mint>::unpack(&data)
+        .map_err(|err| format!("error: invalid mint {mint}: {err}"))?;
+    let supply = TokenAmount {
+        amount: mint_state.base.supply,
+        decimals: mint_state.base.decimals,
+    };
+    Ok((supply, get_permanent_delegate(&mint_state)))
+}
+
+/// Checks that `authority` may spend `amount` from `source`, as its owner, a delegate or the
+/// permanent delegate of the mint, and that it holds enough tokens.
+pub async fn check_source_account(
+    rpc_client: &RpcClient,
+    source: &Pubkey,
+    mint: &Pubkey,
+    authority: &Pubkey,
+    permanent_delegate: Option<Pubkey>,
+    amount: &TokenAmount,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let data = rpc_client
+        .get_account_data(source)
+        .await
+        .map_err(|err| format!("error: unable to get token account {source}: {err}"))?;
+
+    let account = StateWithExtensions::<Account>::unpack(&data)
+        .map_err(|err| format!("error: invalid token account {source}: {err}"))?
+        .base;
+
+    if account.mint != *mint {
+        return Err(format!("error: token account {source} is not for mint {mint}").into());
+    }
+    if account.state == AccountState::Frozen {
+        return Err(format!("error: token account {source} is frozen").into());
+    }
+    if account.amount < amount.amount {
+        let balance = TokenAmount {
+            amount: account.amount,
+            decimals: amount.decimals,
+        };
+        return Err(
+            format!("error: token account {source} holds {balance}, less than {amount}").into(),
+        );
+    }
+
+    let is_owner = account.owner == *authority;
+    let is_delegate =
+        account.delegate == COption::Some(*authority) && account.delegated_amount >= amount.amount;
+    let is_permanent_delegate = permanent_delegate == Some(*authority);
+    if !(is_owner || is_delegate || is_permanent_delegate) {
+        return Err(format!(
+            "error: {authority} is neither the owner, a delegate for {amount} nor the \
+             permanent delegate of token account {source}"
+        )
+        .into());
+    }
+    Ok(())
+}
@@ -0,0 +1,68 @@
+use std::str::FromStr;
+
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+
+/// Token program a mint is created under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenProgram {
+    Legacy,
+    Token2022,
+}
+
+impl TokenProgram {
+    pub fn id(&self) -> Pubkey {
+        match self {
+            TokenProgram::Legacy => spl_token::ID,
+            TokenProgram::Token2022 => spl_token_2022::ID,
+        }
+    }
+}
+
+impl FromStr for TokenProgram {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "legacy" => Ok(TokenProgram::Legacy),
+            "2022" => Ok(TokenProgram::Token2022),
+            _ => Err(format!("expected `legacy` or `2022`, got `{s}`")),
+        }
+    }
+}
+
+/// Returns the id of the token program that owns `mint`, either SPL Token or Token-2022.
+pub async fn get_mint_program(
+    rpc_client: &RpcClient,
+    mint: &Pubkey,
+) -> Result<Pubkey, Box<dyn std::error::Error>> {
+    let account = rpc_client
+        .get_account(mint)
+        .await
+        .map_err(|err| format!("error: unable to get mint {mint}: {err}"))?;
+
+    if account.owner == spl_token::ID || account.owner == spl_token_2022::ID {
+        Ok(account.owner)
+    } else {
+        Err(format!(
+            "error: {mint} is not a mint, it is owned by {}",
+            account.owner
+        )
+        .into())
+    }
+}
+
+/// Returns the id of `token_program` when one was given, otherwise the program that owns `mint`.
+///
+/// Passing the program explicitly skips the lookup, so commands can build transactions for
+/// offline signing without RPC access.
+pub async fn resolve_token_program(
+    rpc_client: &RpcClient,
+    token_program: Option<TokenProgram>,
+    mint: &Pubkey,
+) -> Result<Pubkey, Box<dyn std::error::Error>> {
+    match token_program {
+        Some(token_program) => Ok(token_program.id()),
+        None => get_mint_program(rpc_client, mint).await,
+    }
+}
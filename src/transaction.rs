@@ -0,0 +1,917 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use serde::{Serialize, Serializer};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{
+    RpcSendTransactionConfig, RpcSimulateTransactionConfig, RpcTransactionConfig,
+};
+use solana_sdk::clock::{Slot, MAX_PROCESSING_AGE};
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::hash::Hash;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::Message;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::Signature;
+use solana_sdk::signer::Signer;
+use solana_sdk::transaction::{Transaction, TransactionError};
+use solana_transaction_status::UiTransactionEncoding;
+
+use crate::amount::TokenAmount;
+use crate::nonce_account::get_nonce_account;
+use crate::output::{account_map, display, display_option, display_seq, Report};
+use crate::program_errors::decode_program_error;
+
+const STATUS_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// How often a transaction that has not been seen yet is sent again, in case it was dropped.
+const REBROADCAST_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Blocks a durable nonce transaction is waited for, as long as a recent blockhash stays valid.
+const NONCE_CONFIRM_BLOCKS: u64 = MAX_PROCESSING_AGE as u64;
+
+/// Highest compute unit limit a transaction may request.
+const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// Headroom added on top of the simulated compute units, in percent.
+const COMPUTE_UNIT_LIMIT_MARGIN: u64 = 10;
+
+/// Percentile of recent prioritization fees used as the automatic compute unit price.
+const COMPUTE_UNIT_PRICE_PERCENTILE: usize = 75;
+
+/// Options shared by every command that sends a transaction.
+pub struct TransactionConfig {
+    pub skip_preflight: bool,
+    pub dry_run: bool,
+    pub compute_unit_price: Option<ComputeBudgetSetting<u64>>,
+    pub compute_unit_limit: Option<ComputeBudgetSetting<u32>>,
+    /// Sign with the available signers and print the signatures instead of sending.
+    pub sign_only: bool,
+    /// Blockhash to sign with instead of the latest one from the cluster.
+    pub blockhash: Option<Hash>,
+    pub nonce: Option<NonceConfig>,
+}
+
+/// Durable nonce used in place of a recent blockhash.
+pub struct NonceConfig {
+    pub account: Pubkey,
+    pub authority: Box<dyn Signer>,
+}
+
+/// A compute budget value given on the command line, or `auto` to estimate it from the cluster.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComputeBudgetSetting<T> {
+    Static(T),
+    Auto,
+}
+
+impl<T: FromStr> FromStr for ComputeBudgetSetting<T> {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "auto" {
+            Ok(ComputeBudgetSetting::Auto)
+        } else {
+            s.parse::<T>()
+                .map(ComputeBudgetSetting::Static)
+                .map_err(|_| format!("expected a number or `auto`, got `{s}`"))
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum TransactionOutcome {
+    /// Reached the client commitment without error.
+    Landed,
+    /// Reached the client commitment, but the transaction returned an error.
+    Failed,
+    /// The blockhash expired before the transaction was seen by the cluster.
+    Expired,
+    /// A durable nonce transaction was not seen in time. The nonce has not advanced, so it may
+    /// still land.
+    Unconfirmed,
+    /// Only simulated, nothing was sent.
+    DryRun,
+    /// Signed offline, nothing was sent.
+    SignOnly,
+}
+
+impl fmt::Display for TransactionOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TransactionOutcome::Landed => write!(f, "landed"),
+            TransactionOutcome::Failed => write!(f, "failed"),
+            TransactionOutcome::Expired => write!(f, "expired"),
+            TransactionOutcome::Unconfirmed => write!(f, "unconfirmed, may still land"),
+            TransactionOutcome::DryRun => write!(f, "dry run, not sent"),
+            TransactionOutcome::SignOnly => write!(f, "signed offline, not sent"),
+        }
+    }
+}
+
+/// Result of simulating a transaction before it is sent.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationReport {
+    pub units_consumed: Option<u64>,
+    #[serde(serialize_with = "display_option")]
+    pub error: Option<TransactionError>,
+    pub program_error: Option<String>,
+    pub logs: Vec<String>,
+}
+
+impl fmt::Display for SimulationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Simulation: ")?;
+        match &self.error {
+            Some(err) => write!(f, "failed ({err})")?,
+            None => write!(f, "ok")?,
+        }
+        if let Some(units_consumed) = self.units_consumed {
+            write!(f, "\nCompute units: {units_consumed}")?;
+        }
+        if let Some(program_error) = &self.program_error {
+            write!(f, "\nProgram error: {program_error}")?;
+        }
+        write_logs(f, &self.logs)
+    }
+}
+
+/// Signatures collected in `--sign-only` mode, to be passed back with `--signer` when the
+/// transaction is submitted.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignOnlyReport {
+    #[serde(serialize_with = "display")]
+    pub blockhash: Hash,
+    /// Serialized as `PUBKEY=SIGNATURE` strings, ready to be passed to `--signer`.
+    #[serde(serialize_with = "serialize_signers")]
+    pub signers: Vec<(Pubkey, Signature)>,
+    #[serde(serialize_with = "display_seq")]
+    pub absent_signers: Vec<Pubkey>,
+}
+
+fn serialize_signers<S: Serializer>(
+    signers: &[(Pubkey, Signature)],
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_seq(
+        signers
+            .iter()
+            .map(|(pubkey, signature)| format!("{pubkey}={signature}")),
+    )
+}
+
+impl fmt::Display for SignOnlyReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Blockhash: {}", self.blockhash)?;
+        if !self.signers.is_empty() {
+            write!(f, "\nSigners (Pubkey=Signature):")?;
+            for (pubkey, signature) in &self.signers {
+                write!(f, "\n  {pubkey}={signature}")?;
+            }
+        }
+        if !self.absent_signers.is_empty() {
+            write!(f, "\nAbsent Signers (Pubkey):")?;
+            for pubkey in &self.absent_signers {
+                write!(f, "\n  {pubkey}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Final state of a sent transaction.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionReport {
+    #[serde(serialize_with = "display_option")]
+    pub signature: Option<Signature>,
+    #[serde(rename = "status")]
+    pub outcome: TransactionOutcome,
+    pub slot: Option<Slot>,
+    #[serde(serialize_with = "display_option")]
+    pub error: Option<TransactionError>,
+    pub program_error: Option<String>,
+    pub logs: Vec<String>,
+    /// Accounts created or touched by the command, by role.
+    #[serde(serialize_with = "account_map")]
+    pub accounts: Vec<(&'static str, Pubkey)>,
+    /// Token amount moved by the command.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub amount: Option<TokenAmount>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub simulation: Option<SimulationReport>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compute_unit_price: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compute_unit_limit: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sign_only: Option<SignOnlyReport>,
+    /// The unsigned message, kept for `--dry-run`.
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        serialize_with = "serialize_message"
+    )]
+    pub message: Option<Message>,
+}
+
+impl TransactionReport {
+    fn new(signature: Option<Signature>, outcome: TransactionOutcome) -> Self {
+        TransactionReport {
+            signature,
+            outcome,
+            slot: None,
+            error: None,
+            program_error: None,
+            logs: Vec::new(),
+            accounts: Vec::new(),
+            amount: None,
+            simulation: None,
+            compute_unit_price: None,
+            compute_unit_limit: None,
+            sign_only: None,
+            message: None,
+        }
+    }
+
+    /// Records an account the command created or touched, shown as `name` in the output.
+    pub fn with_account(mut self, name: &'static str, pubkey: Pubkey) -> Self {
+        self.accounts.push((name, pubkey));
+        self
+    }
+
+    pub fn with_amount(mut self, amount: TokenAmount) -> Self {
+        self.amount = Some(amount);
+        self
+    }
+}
+
+impl Report for TransactionReport {
+    fn is_success(&self) -> bool {
+        matches!(
+            self.outcome,
+            TransactionOutcome::Landed | TransactionOutcome::DryRun | TransactionOutcome::SignOnly
+        )
+    }
+}
+
+impl fmt::Display for TransactionReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(message) = &self.message {
+            writeln!(f, "{}", MessageSummary(message))?;
+        }
+        if let Some(simulation) = &self.simulation {
+            writeln!(f, "{simulation}")?;
+        }
+        if let Some(compute_unit_price) = self.compute_unit_price {
+            writeln!(f, "Compute unit price: {compute_unit_price} micro-lamports")?;
+        }
+        if let Some(compute_unit_limit) = self.compute_unit_limit {
+            writeln!(f, "Compute unit limit: {compute_unit_limit}")?;
+        }
+        if let Some(sign_only) = &self.sign_only {
+            writeln!(f, "{sign_only}")?;
+        }
+        if let Some(signature) = self.signature {
+            writeln!(f, "Signature: {signature}")?;
+        }
+        write!(f, "Status: {}", self.outcome)?;
+        if let Some(slot) = self.slot {
+            write!(f, "\nSlot: {slot}")?;
+        }
+        if let Some(err) = &self.error {
+            write!(f, "\nError: {err}")?;
+        }
+        if let Some(program_error) = &self.program_error {
+            write!(f, "\nProgram error: {program_error}")?;
+        }
+        for (name, pubkey) in &self.accounts {
+            write!(f, "\n{name}: {pubkey}")?;
+        }
+        if let Some(amount) = &self.amount {
+            write!(f, "\nAmount: {amount}")?;
+        }
+        write_logs(f, &self.logs)
+    }
+}
+
+/// Human readable listing of a message: fee payer, then each instruction with its program,
+/// accounts and data.
+pub struct MessageSummary<'a>(pub &'a Message);
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountSummary {
+    #[serde(serialize_with = "display")]
+    pubkey: Pubkey,
+    is_signer: bool,
+    is_writable: bool,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InstructionSummary {
+    #[serde(serialize_with = "display")]
+    program_id: Pubkey,
+    accounts: Vec<AccountSummary>,
+    /// Hex encoded instruction data.
+    data: String,
+}
+
+impl MessageSummary<'_> {
+    fn instructions(&self) -> Vec<InstructionSummary> {
+        let message = self.0;
+        message
+            .instructions
+            .iter()
+            .map(|ix| InstructionSummary {
+                program_id: message.account_keys[ix.program_id_index as usize],
+                accounts: ix
+                    .accounts
+                    .iter()
+                    .map(|index| AccountSummary {
+                        pubkey: message.account_keys[*index as usize],
+                        is_signer: message.is_signer(*index as usize),
+                        is_writable: message.is_writable(*index as usize),
+                    })
+                    .collect(),
+                data: ix.data.iter().map(|byte| format!("{byte:02x}")).collect(),
+            })
+            .collect()
+    }
+}
+
+impl fmt::Display for MessageSummary<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(fee_payer) = self.0.account_keys.first() {
+            write!(f, "Fee payer: {fee_payer}")?;
+        }
+        for (i, ix) in self.instructions().iter().enumerate() {
+            write!(f, "\nInstruction {i}")?;
+            write!(f, "\n  Program: {}", ix.program_id)?;
+            for (j, account) in ix.accounts.iter().enumerate() {
+                let mut flags = Vec::new();
+                if account.is_signer {
+                    flags.push("signer");
+                }
+                if account.is_writable {
+                    flags.push("writable");
+                }
+                write!(
+                    f,
+                    "\n  Account {j}: {} [{}]",
+                    account.pubkey,
+                    flags.join(", ")
+                )?;
+            }
+            write!(f, "\n  Data: {}", ix.data)?;
+        }
+        Ok(())
+    }
+}
+
+impl Serialize for MessageSummary<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        #[derive(Serialize)]
+        #[serde(rename_all = "camelCase")]
+        struct Summary {
+            #[serde(serialize_with = "display_option")]
+            fee_payer: Option<Pubkey>,
+            instructions: Vec<InstructionSummary>,
+        }
+
+        Summary {
+            fee_payer: self.0.account_keys.first().copied(),
+            instructions: self.instructions(),
+        }
+        .serialize(serializer)
+    }
+}
+
+fn serialize_message<S: Serializer>(
+    message: &Option<Message>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    message.as_ref().map(MessageSummary).serialize(serializer)
+}
+
+fn write_logs(f: &mut fmt::Formatter, logs: &[String]) -> fmt::Result {
+    if !logs.is_empty() {
+        write!(f, "\nLogs:")?;
+        for log in logs {
+            write!(f, "\n  {log}")?;
+        }
+    }
+    Ok(())
+}
+
+/// Builds a transaction from `instructions` paid for by `fee_payer`, signs it with
+/// `fee_payer` and `signers`, sends it and waits until it reaches the client commitment or
+/// its blockhash expires.
+///
+/// Unless `skip_preflight` is set the transaction is simulated first, and nothing is sent if
+/// the simulation fails. With `dry_run` the transaction is printed and simulated without
+/// being signed or sent, and with `sign_only` it is signed with `blockhash` and the
+/// signatures are returned instead of sending it. Compute budget instructions from
+/// `tx_config` are prepended to `instructions`. With a `nonce` the transaction advances the
+/// nonce account and uses its value as the blockhash.
+pub async fn execute_transaction(
+    rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
+    instructions: &[Instruction],
+    fee_payer: &dyn Signer,
+    signers: &[&dyn Signer],
+) -> Result<TransactionReport, Box<dyn std::error::Error>> {
+    execute_transaction_with_hook(
+        rpc_client,
+        tx_config,
+        instructions,
+        fee_payer,
+        signers,
+        &|_, _| Ok(()),
+    )
+    .await
+}
+
+/// Called with the signature and blockhash of a transaction right before it is sent.
+pub type BeforeSendHook<'a> =
+    dyn Fn(&Signature, &Hash) -> Result<(), Box<dyn std::error::Error>> + 'a;
+
+/// Same as [`execute_transaction`], but calls `before_send` with the signature and blockhash
+/// of the signed transaction right before it is sent, after the preflight simulation passed.
+/// Callers that must not send twice record them there, so after a crash they can check
+/// whether the transaction landed.
+pub async fn execute_transaction_with_hook(
+    rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
+    instructions: &[Instruction],
+    fee_payer: &dyn Signer,
+    signers: &[&dyn Signer],
+    before_send: &BeforeSendHook<'_>,
+) -> Result<TransactionReport, Box<dyn std::error::Error>> {
+    // The same key may be passed in several roles, it only has to sign once.
+    let mut unique_signers: Vec<&dyn Signer> = vec![fee_payer];
+    let nonce_authority = tx_config
+        .nonce
+        .as_ref()
+        .map(|nonce| nonce.authority.as_ref());
+    for signer in signers.iter().copied().chain(nonce_authority) {
+        if !unique_signers.iter().any(|s| s.pubkey() == signer.pubkey()) {
+            unique_signers.push(signer);
+        }
+    }
+
+    let (message, compute_budget) =
+        build_message(rpc_client, tx_config, instructions, &fee_payer.pubkey()).await?;
+
+    let mut tx = Transaction::new_unsigned(message);
+
+    if tx_config.dry_run {
+        let simulation = simulate(rpc_client, &tx).await?;
+        if simulation.error.is_some() {
+            return Err(format!(
+                "error: transaction simulation failed\n{}\n{simulation}",
+                MessageSummary(&tx.message)
+            )
+            .into());
+        }
+        let mut report = TransactionReport::new(None, TransactionOutcome::DryRun);
+        report.simulation = Some(simulation);
+        report.message = Some(tx.message);
+        report.compute_unit_price = compute_budget.compute_unit_price;
+        report.compute_unit_limit = compute_budget.compute_unit_limit;
+        return Ok(report);
+    }
+
+    let blockhash = match (tx_config.blockhash, &tx_config.nonce) {
+        (Some(blockhash), _) => blockhash,
+        (None, Some(nonce)) => get_nonce_account(rpc_client, &nonce.account)
+            .await?
+            .data
+            .blockhash(),
+        (None, None) => get_blockhash(rpc_client).await?,
+    };
+
+    // Offline signers only hold a pubkey and leave their signature empty, so a sign-only
+    // transaction is allowed to be partially signed.
+    let sign_result = if tx_config.sign_only {
+        tx.try_partial_sign(&unique_signers, blockhash)
+    } else {
+        tx.try_sign(&unique_signers, blockhash)
+    };
+    sign_result.map_err(|err| format!("error: failed to sign transaction: {err}"))?;
+
+    if tx_config.sign_only {
+        let mut report = TransactionReport::new(None, TransactionOutcome::SignOnly);
+        report.sign_only = Some(collect_signatures(&tx));
+        report.compute_unit_price = compute_budget.compute_unit_price;
+        report.compute_unit_limit = compute_budget.compute_unit_limit;
+        return Ok(report);
+    }
+
+    let simulation = if tx_config.skip_preflight {
+        None
+    } else {
+        let simulation = simulate(rpc_client, &tx).await?;
+        if simulation.error.is_some() {
+            return Err(format!("error: transaction simulation failed\n{simulation}").into());
+        }
+        Some(simulation)
+    };
+
+    before_send(&tx.signatures[0], &blockhash)?;
+
+    let signature = send(rpc_client, tx_config, &tx).await?;
+
+    let mut report = confirm(rpc_client, tx_config, &tx, signature, &blockhash).await?;
+    report.simulation = simulation;
+    report.compute_unit_price = compute_budget.compute_unit_price;
+    report.compute_unit_limit = compute_budget.compute_unit_limit;
+
+    Ok(report)
+}
+
+/// Creates a message for `instructions`, led by an advance nonce instruction when a durable
+/// nonce is configured.
+fn new_message(
+    tx_config: &TransactionConfig,
+    instructions: &[Instruction],
+    fee_payer: &Pubkey,
+) -> Message {
+    match &tx_config.nonce {
+        Some(nonce) => Message::new_with_nonce(
+            instructions.to_vec(),
+            Some(fee_payer),
+            &nonce.account,
+            &nonce.authority.pubkey(),
+        ),
+        None => Message::new(instructions, Some(fee_payer)),
+    }
+}
+
+/// Compute budget values that ended up in a transaction.
+#[derive(Default)]
+struct ComputeBudget {
+    compute_unit_price: Option<u64>,
+    compute_unit_limit: Option<u32>,
+}
+
+async fn build_message(
+    rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
+    instructions: &[Instruction],
+    fee_payer: &Pubkey,
+) -> Result<(Message, ComputeBudget), Box<dyn std::error::Error>> {
+    let mut compute_budget = ComputeBudget::default();
+    let mut budget_ixs = Vec::new();
+
+    // Offline signatures only stay valid if the submitting run builds the exact same message.
+    let auto_budget = tx_config.compute_unit_price == Some(ComputeBudgetSetting::Auto)
+        || tx_config.compute_unit_limit == Some(ComputeBudgetSetting::Auto);
+    if auto_budget && tx_config.blockhash.is_some() {
+        return Err(
+            "error: `auto` compute budget values cannot be combined with --blockhash".into(),
+        );
+    }
+
+    if let Some(setting) = tx_config.compute_unit_price {
+        let micro_lamports = match setting {
+            ComputeBudgetSetting::Static(micro_lamports) => micro_lamports,
+            ComputeBudgetSetting::Auto => {
+                estimate_compute_unit_price(rpc_client, instructions, fee_payer).await?
+            }
+        };
+        budget_ixs.push(ComputeBudgetInstruction::set_compute_unit_price(
+            micro_lamports,
+        ));
+        compute_budget.compute_unit_price = Some(micro_lamports);
+    }
+
+    if let Some(setting) = tx_config.compute_unit_limit {
+        let units = match setting {
+            ComputeBudgetSetting::Static(units) => units,
+            ComputeBudgetSetting::Auto => {
+                estimate_compute_unit_limit(
+                    rpc_client,
+                    tx_config,
+                    &budget_ixs,
+                    instructions,
+                    fee_payer,
+                )
+                .await?
+            }
+        };
+        budget_ixs.push(ComputeBudgetInstruction::set_compute_unit_limit(units));
+        compute_budget.compute_unit_limit = Some(units);
+    }
+
+    let message = new_message(
+        tx_config,
+        &[budget_ixs, instructions.to_vec()].concat(),
+        fee_payer,
+    );
+
+    Ok((message, compute_budget))
+}
+
+/// Picks a compute unit price from the fees recently paid to lock the accounts written by
+/// `instructions`.
+async fn estimate_compute_unit_price(
+    rpc_client: &RpcClient,
+    instructions: &[Instruction],
+    fee_payer: &Pubkey,
+) -> Result<u64, Box<dyn std::error::Error>> {
+    let mut writable_accounts = vec![*fee_payer];
+    for account in instructions.iter().flat_map(|ix| &ix.accounts) {
+        if account.is_writable && !writable_accounts.contains(&account.pubkey) {
+            writable_accounts.push(account.pubkey);
+        }
+    }
+
+    let mut fees: Vec<u64> = rpc_client
+        .get_recent_prioritization_fees(&writable_accounts)
+        .await
+        .map_err(|err| format!("error: get recent prioritization fees: {err}"))?
+        .into_iter()
+        .map(|fee| fee.prioritization_fee)
+        .collect();
+
+    if fees.is_empty() {
+        return Ok(0);
+    }
+
+    fees.sort_unstable();
+
+    Ok(fees[(fees.len() - 1) * COMPUTE_UNIT_PRICE_PERCENTILE / 100])
+}
+
+/// Simulates `instructions` with the maximum compute unit limit and returns the units they
+/// consumed plus a safety margin.
+async fn estimate_compute_unit_limit(
+    rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
+    budget_ixs: &[Instruction],
+    instructions: &[Instruction],
+    fee_payer: &Pubkey,
+) -> Result<u32, Box<dyn std::error::Error>> {
+    let mut estimate_ixs = budget_ixs.to_vec();
+    estimate_ixs.push(ComputeBudgetInstruction::set_compute_unit_limit(
+        MAX_COMPUTE_UNIT_LIMIT,
+    ));
+    estimate_ixs.extend_from_slice(instructions);
+
+    let tx = Transaction::new_unsigned(new_message(tx_config, &estimate_ixs, fee_payer));
+
+    let simulation = simulate(rpc_client, &tx).await?;
+    if simulation.error.is_some() {
+        return Err(format!("error: transaction simulation failed\n{simulation}").into());
+    }
+
+    let units_consumed = simulation
+        .units_consumed
+        .ok_or("error: simulation did not report consumed compute units")?;
+
+    let units = units_consumed * (100 + COMPUTE_UNIT_LIMIT_MARGIN) / 100;
+
+    Ok(units.min(MAX_COMPUTE_UNIT_LIMIT as u64) as u32)
+}
+
+async fn get_blockhash(rpc_client: &RpcClient) -> Result<Hash, Box<dyn std::error::Error>> {
+    let blockhash = rpc_client
+        .get_latest_blockhash()
+        .await
+        .map_err(|err| format!("error: unable to get latest blockhash: {err}"))?;
+
+    Ok(blockhash)
+}
+
+/// Splits the signatures of `tx` into the ones present and the signers still missing. Offline
+/// signers that only know their pubkey leave a default signature behind.
+fn collect_signatures(tx: &Transaction) -> SignOnlyReport {
+    let mut signers = Vec::new();
+    let mut absent_signers = Vec::new();
+
+    let signer_keys =
+        &tx.message.account_keys[..tx.message.header.num_required_signatures as usize];
+    for (pubkey, signature) in signer_keys.iter().zip(&tx.signatures) {
+        if *signature == Signature::default() {
+            absent_signers.push(*pubkey);
+        } else {
+            signers.push((*pubkey, *signature));
+        }
+    }
+
+    SignOnlyReport {
+        blockhash: tx.message.recent_blockhash,
+        signers,
+        absent_signers,
+    }
+}
+
+async fn simulate(
+    rpc_client: &RpcClient,
+    tx: &Transaction,
+) -> Result<SimulationReport, Box<dyn std::error::Error>> {
+    // Unsigned dry-run transactions carry no blockhash, let the node fill in its own.
+    let config = RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: tx.message.recent_blockhash == Hash::default(),
+        commitment: Some(rpc_client.commitment()),
+        ..RpcSimulateTransactionConfig::default()
+    };
+
+    let result = rpc_client
+        .simulate_transaction_with_config(tx, config)
+        .await
+        .map_err(|err| format!("error: simulate transaction: {err}"))?
+        .value;
+
+    let logs = result.logs.unwrap_or_default();
+    let program_error = result
+        .err
+        .as_ref()
+        .and_then(|err| decode_program_error(&tx.message, err, &logs));
+
+    Ok(SimulationReport {
+        units_consumed: result.units_consumed,
+        error: result.err,
+        program_error,
+        logs,
+    })
+}
+
+async fn send(
+    rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
+    tx: &Transaction,
+) -> Result<Signature, Box<dyn std::error::Error>> {
+    let config = RpcSendTransactionConfig {
+        skip_preflight: tx_config.skip_preflight,
+        ..RpcSendTransactionConfig::default()
+    };
+
+    let signature = rpc_client
+        .send_transaction_with_config(tx, config)
+        .await
+        .map_err(|err| format!("error: send transaction: {err}"))?;
+
+    Ok(signature)
+}
+
+/// Waits for `tx` to reach the client commitment, sending it again while it is not seen.
+///
+/// A durable nonce never expires while the transaction is unseen, so it is only waited for
+/// [`NONCE_CONFIRM_BLOCKS`] blocks and reported as unconfirmed after that.
+async fn confirm(
+    rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
+    tx: &Transaction,
+    signature: Signature,
+    blockhash: &Hash,
+) -> Result<TransactionReport, Box<dyn std::error::Error>> {
+    let message = &tx.message;
+    // The signature status and the blockhash are not read atomically, so expiry has to be
+    // seen twice in a row before the transaction is given up on.
+    let mut expiry_seen = false;
+    let mut last_sent = Instant::now();
+    let last_valid_block_height = match &tx_config.nonce {
+        Some(_) => Some(get_block_height(rpc_client).await? + NONCE_CONFIRM_BLOCKS),
+        None => None,
+    };
+
+    loop {
+        let status = rpc_client
+            .get_signature_statuses(&[signature])
+            .await
+            .map_err(|err| format!("error: get signature status: {err}"))?
+            .value
+            .pop()
+            .flatten();
+
+        match status {
+            Some(status) if status.satisfies_commitment(rpc_client.commitment()) => {
+                let (outcome, logs) = if status.err.is_some() {
+                    (
+                        TransactionOutcome::Failed,
+                        get_logs(rpc_client, &signature).await?,
+                    )
+                } else {
+                    (TransactionOutcome::Landed, Vec::new())
+                };
+                let program_error = status
+                    .err
+                    .as_ref()
+                    .and_then(|err| decode_program_error(message, err, &logs));
+
+                let mut report = TransactionReport::new(Some(signature), outcome);
+                report.slot = Some(status.slot);
+                report.error = status.err;
+                report.program_error = program_error;
+                report.logs = logs;
+                return Ok(report);
+            }
+            Some(_) => {}
+            None => {
+                if is_blockhash_expired(rpc_client, tx_config, blockhash).await? {
+                    if expiry_seen {
+                        return Ok(TransactionReport::new(
+                            Some(signature),
+                            TransactionOutcome::Expired,
+                        ));
+                    }
+                    expiry_seen = true;
+                    continue;
+                }
+
+                if let (Some(nonce), Some(last_valid_block_height)) =
+                    (&tx_config.nonce, last_valid_block_height)
+                {
+                    if get_block_height(rpc_client).await? > last_valid_block_height {
+                        eprintln!(
+                            "warning: transaction {signature} was not confirmed within \
+                             {NONCE_CONFIRM_BLOCKS} blocks. Nonce account {} has not advanced, \
+                             so the transaction may still land. Running the same command again \
+                             resubmits it with the same nonce.",
+                            nonce.account
+                        );
+                        return Ok(TransactionReport::new(
+                            Some(signature),
+                            TransactionOutcome::Unconfirmed,
+                        ));
+                    }
+                }
+
+                if last_sent.elapsed() >= REBROADCAST_INTERVAL {
+                    resend(rpc_client, tx).await;
+                    last_sent = Instant::now();
+                }
+            }
+        }
+
+        tokio::time::sleep(STATUS_POLL_INTERVAL).await;
+    }
+}
+
+/// Sends `tx` again without preflight. Errors are ignored, the transaction may already have
+/// been processed and its status is checked separately.
+async fn resend(rpc_client: &RpcClient, tx: &Transaction) {
+    let config = RpcSendTransactionConfig {
+        skip_preflight: true,
+        ..RpcSendTransactionConfig::default()
+    };
+    let _ = rpc_client.send_transaction_with_config(tx, config).await;
+}
+
+async fn get_block_height(rpc_client: &RpcClient) -> Result<u64, Box<dyn std::error::Error>> {
+    let block_height = rpc_client
+        .get_block_height()
+        .await
+        .map_err(|err| format!("error: unable to get block height: {err}"))?;
+
+    Ok(block_height)
+}
+
+/// A nonce transaction expires once the nonce account moves past `blockhash`, any other
+/// transaction once `blockhash` is too old.
+async fn is_blockhash_expired(
+    rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
+    blockhash: &Hash,
+) -> Result<bool, Box<dyn std::error::Error>> {
+    if let Some(nonce) = &tx_config.nonce {
+        let nonce_account = get_nonce_account(rpc_client, &nonce.account).await?;
+        return Ok(nonce_account.data.blockhash() != *blockhash);
+    }
+
+    let blockhash_valid = rpc_client
+        .is_blockhash_valid(blockhash, rpc_client.commitment())
+        .await
+        .map_err(|err| format!("error: check blockhash: {err}"))?;
+
+    Ok(!blockhash_valid)
+}
+
+async fn get_logs(
+    rpc_client: &RpcClient,
+    signature: &Signature,
+) -> Result<Vec<String>, Box<dyn std::error::Error>> {
+    let config = RpcTransactionConfig {
+        encoding: Some(UiTransactionEncoding::Json),
+        commitment: Some(rpc_client.commitment()),
+        max_supported_transaction_version: Some(0),
+    };
+
+    let tx = rpc_client
+        .get_transaction_with_config(signature, config)
+        .await
+        .map_err(|err| format!("error: get transaction: {err}"))?;
+
+    Ok(tx
+        .transaction
+        .meta
+        .and_then(|meta| Option::from(meta.log_messages))
+        .unwrap_or_default())
+}
@@ -0,0 +1,231 @@
+use solana_account_decoder::UiAccountEncoding;
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
+use spl_token_2022::extension::transfer_fee::instruction::{
+    harvest_withheld_tokens_to_mint, set_transfer_fee, withdraw_withheld_tokens_from_accounts,
+    withdraw_withheld_tokens_from_mint,
+};
+use spl_token_2022::extension::transfer_fee::TransferFeeAmount;
+use spl_token_2022::extension::{BaseStateWithExtensions, StateWithExtensions};
+use spl_token_2022::state::Account;
+
+use crate::output::Report;
+use crate::token_program::{resolve_token_program, TokenProgram};
+use crate::transaction::{execute_transaction, TransactionConfig, TransactionReport};
+
+/// Source accounts per harvest or withdraw transaction, which keeps it under the size limit.
+const MAX_SOURCE_ACCOUNTS_PER_TRANSACTION: usize = 20;
+
+async fn require_token_2022(
+    rpc_client: &RpcClient,
+    token_program: Option<TokenProgram>,
+    mint: &Pubkey,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let token_program = resolve_token_program(rpc_client, token_program, mint).await?;
+    if token_program != spl_token_2022::ID {
+        return Err(format!("error: {mint} is not a Token-2022 mint with transfer fees").into());
+    }
+    Ok(())
+}
+
+/// Returns every token account of `mint` that holds withheld transfer fees.
+pub async fn get_accounts_with_withheld_fees(
+    rpc_client: &RpcClient,
+    mint: &Pubkey,
+) -> Result<Vec<Pubkey>, Box<dyn std::error::Error>> {
+    let accounts = rpc_client
+        .get_program_accounts_with_config(
+            &spl_token_2022::ID,
+            RpcProgramAccountsConfig {
+                filters: Some(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                    0,
+                    mint.as_ref(),
+                ))]),
+                account_config: RpcAccountInfoConfig {
+                    encoding: Some(UiAccountEncoding::Base64),
+                    ..RpcAccountInfoConfig::default()
+                },
+                ..RpcProgramAccountsConfig::default()
+            },
+        )
+        .await
+        .map_err(|err| format!("error: unable to get token accounts of {mint}: {err}"))?;
+
+    Ok(accounts
+        .into_iter()
+        .filter(|(_, account)| {
+            StateWithExtensions::<Account>::unpack(&account.data)
+                .ok()
+                .and_then(|state| {
+                    state
+                        .get_extension::<TransferFeeAmount>()
+                        .ok()
+                        .map(|fee| u64::from(fee.withheld_amount) > 0)
+                })
+                .unwrap_or(false)
+        })
+        .map(|(pubkey, _)| pubkey)
+        .collect())
+}
+
+/// Moves the fees withheld in every token account of `mint` into the mint, in batches.
+///
+/// Harvesting is permissionless, so only the fee payer signs.
+pub async fn process_harvest_withheld_fees(
+    rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
+    fee_payer: &dyn Signer,
+    mint_pubkey: Pubkey,
+    token_program: Option<TokenProgram>,
+) -> Result<Vec<TransactionReport>, Box<dyn std::error::Error>> {
+    require_token_2022(rpc_client, token_program, &mint_pubkey).await?;
+
+    let sources = get_accounts_with_withheld_fees(rpc_client, &mint_pubkey).await?;
+
+    let mut reports = Vec::new();
+    for batch in sources.chunks(MAX_SOURCE_ACCOUNTS_PER_TRANSACTION) {
+        let harvest_ix = harvest_withheld_tokens_to_mint(
+            &spl_token_2022::ID,
+            &mint_pubkey,
+            &batch.iter().collect::<Vec<_>>(),
+        )?;
+
+        let report = execute_transaction(rpc_client, tx_config, &[harvest_ix], fee_payer, &[])
+            .await?
+            .with_account("mint", mint_pubkey);
+        let is_success = report.is_success();
+        reports.push(report);
+        if !is_success {
+            break;
+        }
+    }
+    Ok(reports)
+}
+
+/// Withdraws the fees withheld in the mint to the `treasury` associated token account,
+/// creating it if needed.
+#[allow(clippy::too_many_arguments)]
+pub async fn process_withdraw_withheld_fees_from_mint(
+    rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
+    signer: &dyn Signer,
+    fee_payer: &dyn Signer,
+    mint_pubkey: Pubkey,
+    treasury: Pubkey,
+    token_program: Option<TokenProgram>,
+) -> Result<TransactionReport, Box<dyn std::error::Error>> {
+    require_token_2022(rpc_client, token_program, &mint_pubkey).await?;
+
+    let treasury_ata =
+        get_associated_token_address_with_program_id(&treasury, &mint_pubkey, &spl_token_2022::ID);
+
+    let instructions = vec![
+        create_associated_token_account_idempotent(
+            &fee_payer.pubkey(),
+            &treasury,
+            &mint_pubkey,
+            &spl_token_2022::ID,
+        ),
+        withdraw_withheld_tokens_from_mint(
+            &spl_token_2022::ID,
+            &mint_pubkey,
+            &treasury_ata,
+            &signer.pubkey(),
+            &[],
+        )?,
+    ];
+
+    execute_transaction(rpc_client, tx_config, &instructions, fee_payer, &[signer])
+        .await
+        .map(|report| {
+            report
+                .with_account("mint", mint_pubkey)
+                .with_account("treasuryTokenAccount", treasury_ata)
+        })
+}
+
+/// Withdraws the fees withheld in every token account of `mint` directly to the `treasury`
+/// associated token account, in batches.
+#[allow(clippy::too_many_arguments)]
+pub async fn process_withdraw_withheld_fees_from_accounts(
+    rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
+    signer: &dyn Signer,
+    fee_payer: &dyn Signer,
+    mint_pubkey: Pubkey,
+    treasury: Pubkey,
+    token_program: Option<TokenProgram>,
+) -> Result<Vec<TransactionReport>, Box<dyn std::error::Error>> {
+    require_token_2022(rpc_client, token_program, &mint_pubkey).await?;
+
+    let treasury_ata =
+        get_associated_token_address_with_program_id(&treasury, &mint_pubkey, &spl_token_2022::ID);
+
+    let sources = get_accounts_with_withheld_fees(rpc_client, &mint_pubkey).await?;
+
+    let mut reports = Vec::new();
+    for batch in sources.chunks(MAX_SOURCE_ACCOUNTS_PER_TRANSACTION) {
+        let instructions = vec![
+            create_associated_token_account_idempotent(
+                &fee_payer.pubkey(),
+                &treasury,
+                &mint_pubkey,
+                &spl_token_2022::ID,
+            ),
+            withdraw_withheld_tokens_from_accounts(
+                &spl_token_2022::ID,
+                &mint_pubkey,
+                &treasury_ata,
+                &signer.pubkey(),
+                &[],
+                &batch.iter().collect::<Vec<_>>(),
+            )?,
+        ];
+
+        let report =
+            execute_transaction(rpc_client, tx_config, &instructions, fee_payer, &[signer])
+                .await?
+                .with_account("mint", mint_pubkey)
+                .with_account("treasuryTokenAccount", treasury_ata);
+        let is_success = report.is_success();
+        reports.push(report);
+        if !is_success {
+            break;
+        }
+    }
+    Ok(reports)
+}
+
+/// Sets a new transfer fee through the transfer fee config authority. It takes effect two
+/// epochs later.
+#[allow(clippy::too_many_arguments)]
+pub async fn process_update_transfer_fee(
+    rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
+    signer: &dyn Signer,
+    fee_payer: &dyn Signer,
+    mint_pubkey: Pubkey,
+    basis_points: u16,
+    maximum_fee: u64,
+    token_program: Option<TokenProgram>,
+) -> Result<TransactionReport, Box<dyn std::error::Error>> {
+    require_token_2022(rpc_client, token_program, &mint_pubkey).await?;
+
+    let set_fee_ix = set_transfer_fee(
+        &spl_token_2022::ID,
+        &mint_pubkey,
+        &signer.pubkey(),
+        &[],
+        basis_points,
+        maximum_fee,
+    )?;
+
+    execute_transaction(rpc_client, tx_config, &[set_fee_ix], fee_payer, &[signer])
+        .await
+        .map(|report| report.with_account("mint", mint_pubkey))
+}
@@ -1,55 +1,261 @@
-use mpl_token_metadata::accounts::Metadata;
+use mpl_token_metadata::accounts::{MasterEdition, Metadata, TokenRecord};
 use mpl_token_metadata::instructions::TransferV1Builder;
+use mpl_token_metadata::types::TokenStandard;
 use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_client::rpc_config::RpcSendTransactionConfig;
-use solana_sdk::message::Message;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::program_error::ProgramError;
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::transaction::Transaction;
-use solana_sdk::{signature::Signature, signer::Signer};
-use spl_associated_token_account::get_associated_token_address;
+use solana_sdk::signer::Signer;
+use solana_sdk::{system_program, sysvar};
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_associated_token_account::instruction::create_associated_token_account_idempotent;
 
-pub async fn process_transfer_to(
+use crate::amount::{resolve_mint_decimals, Amount};
+use crate::memo::Annotation;
+use crate::metadata::{rule_set_of, MPL_TOKEN_AUTH_RULES_ID};
+use crate::token_account::{check_source_account, get_mint_supply};
+use crate::token_program::{resolve_token_program, TokenProgram};
+use crate::transaction::{execute_transaction, TransactionConfig, TransactionReport};
+
+/// Returns the Token Metadata account of `mint` when its token standard only allows transfers
+/// through Token Metadata, which is the case for programmable NFTs.
+pub async fn get_programmable_metadata(
     rpc_client: &RpcClient,
-    signer: &dyn Signer,
-    mint_pubkey: Pubkey,
-    receiver_pubkey: Pubkey,
+    mint: &Pubkey,
+) -> Result<Option<Metadata>, Box<dyn std::error::Error>> {
+    let (metadata_pubkey, _) = Metadata::find_pda(mint);
+    let account = rpc_client
+        .get_account_with_commitment(&metadata_pubkey, rpc_client.commitment())
+        .await
+        .map_err(|err| format!("error: unable to get metadata account {metadata_pubkey}: {err}"))?
+        .value;
+
+    let Some(account) = account else {
+        return Ok(None);
+    };
+    let metadata = Metadata::safe_deserialize(&account.data)
+        .map_err(|err| format!("error: invalid metadata account {metadata_pubkey}: {err}"))?;
+
+    Ok(matches!(
+        metadata.token_standard,
+        Some(TokenStandard::ProgrammableNonFungible)
+            | Some(TokenStandard::ProgrammableNonFungibleEdition)
+    )
+    .then_some(metadata))
+}
+
+/// Instructions transferring `amount` base units from the associated token account of
+/// `source_owner` to the one of `receiver` with `transfer_checked`, creating the receiver's
+/// account if needed. `authority` is the owner or a delegate of the source account.
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_checked_instructions(
+    authority: &Pubkey,
+    source_owner: &Pubkey,
+    fee_payer: &Pubkey,
+    mint: &Pubkey,
+    receiver: &Pubkey,
+    amount: u64,
+    decimals: u8,
+    token_program: &Pubkey,
+) -> Result<Vec<Instruction>, ProgramError> {
+    let receiver_ata = get_associated_token_address_with_program_id(receiver, mint, token_program);
+    let source_ata =
+        get_associated_token_address_with_program_id(source_owner, mint, token_program);
+
+    Ok(vec![
+        create_associated_token_account_idempotent(fee_payer, receiver, mint, token_program),
+        spl_token_2022::instruction::transfer_checked(
+            token_program,
+            &source_ata,
+            mint,
+            &receiver_ata,
+            authority,
+            &[],
+            amount,
+            decimals,
+        )?,
+    ])
+}
+
+/// Token Metadata `TransferV1` instruction for programmable NFTs, which also moves their
+/// token records and runs the rule set.
+pub fn programmable_transfer_instruction(
+    signer: &Pubkey,
+    fee_payer: &Pubkey,
+    metadata: &Metadata,
+    receiver: &Pubkey,
     amount: u64,
-) -> Result<Signature, Box<dyn std::error::Error>> {
-    let (metadata, _) = Metadata::find_pda(&mint_pubkey);
-    let receiver_ata = get_associated_token_address(&receiver_pubkey, &mint_pubkey);
-    let signer_ata = get_associated_token_address(&signer.pubkey(), &mint_pubkey);
+    token_program: &Pubkey,
+) -> Instruction {
+    let mint = &metadata.mint;
+    let (metadata_pubkey, _) = Metadata::find_pda(mint);
+    let (edition, _) = MasterEdition::find_pda(mint);
+    let receiver_ata = get_associated_token_address_with_program_id(receiver, mint, token_program);
+    let signer_ata = get_associated_token_address_with_program_id(signer, mint, token_program);
+    let (token_record, _) = TokenRecord::find_pda(mint, &signer_ata);
+    let (destination_token_record, _) = TokenRecord::find_pda(mint, &receiver_ata);
+    let rule_set = rule_set_of(metadata);
 
-    let transfer_to_ix = TransferV1Builder::new()
+    TransferV1Builder::new()
         .token(signer_ata)
-        .token_owner(signer.pubkey())
+        .token_owner(*signer)
         .destination_token(receiver_ata)
-        .destination_owner(receiver_pubkey)
-        .metadata(metadata)
-        .mint(mint_pubkey)
+        .destination_owner(*receiver)
+        .metadata(metadata_pubkey)
+        .mint(*mint)
+        .edition(Some(edition))
+        .token_record(Some(token_record))
+        .destination_token_record(Some(destination_token_record))
         .amount(amount)
-        .authority(signer.pubkey())
-        .payer(signer.pubkey())
-        .instruction();
+        .authority(*signer)
+        .payer(*fee_payer)
+        .system_program(system_program::ID)
+        .sysvar_instructions(sysvar::instructions::ID)
+        .spl_token_program(*token_program)
+        .spl_ata_program(spl_associated_token_account::ID)
+        .authorization_rules_program(rule_set.map(|_| MPL_TOKEN_AUTH_RULES_ID))
+        .authorization_rules(rule_set)
+        .instruction()
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn process_transfer_to(
+    rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
+    signer: &dyn Signer,
+    fee_payer: &dyn Signer,
+    mint_pubkey: Pubkey,
+    receiver_pubkey: Pubkey,
+    amount: &Amount,
+    token_program: Option<TokenProgram>,
+    mint_decimals: Option<u8>,
+    annotation: &Annotation,
+) -> Result<TransactionReport, Box<dyn std::error::Error>> {
+    let token_program = resolve_token_program(rpc_client, token_program, &mint_pubkey).await?;
+    let decimals =
+        resolve_mint_decimals(rpc_client, mint_decimals, tx_config.sign_only, &mint_pubkey).await?;
+    let amount = amount
+        .to_token_amount(decimals)
+        .map_err(|err| format!("error: {err}"))?;
+    let receiver_ata = get_associated_token_address_with_program_id(
+        &receiver_pubkey,
+        &mint_pubkey,
+        &token_program,
+    );
+    let signer_ata = get_associated_token_address_with_program_id(
+        &signer.pubkey(),
+        &mint_pubkey,
+        &token_program,
+    );
 
-    let mut tx = Transaction::new_unsigned(Message::new(&[transfer_to_ix], Some(&signer.pubkey())));
+    // Programmable NFT transfers need the rule set from the metadata account, so offline
+    // transfers skip the lookup and are built with `transfer_checked`.
+    let programmable_metadata = if tx_config.sign_only {
+        None
+    } else {
+        get_programmable_metadata(rpc_client, &mint_pubkey).await?
+    };
+    let mut transfer_ixs = match programmable_metadata {
+        Some(metadata) => vec![programmable_transfer_instruction(
+            &signer.pubkey(),
+            &fee_payer.pubkey(),
+            &metadata,
+            &receiver_pubkey,
+            amount.amount,
+            &token_program,
+        )],
+        None => transfer_checked_instructions(
+            &signer.pubkey(),
+            &signer.pubkey(),
+            &fee_payer.pubkey(),
+            &mint_pubkey,
+            &receiver_pubkey,
+            amount.amount,
+            decimals,
+            &token_program,
+        )?,
+    };
+    annotation.apply(&mut transfer_ixs, &signer.pubkey());
 
-    let blockhash = rpc_client
-        .get_latest_blockhash()
+    execute_transaction(rpc_client, tx_config, &transfer_ixs, fee_payer, &[signer])
         .await
-        .map_err(|err| format!("error: unable to get latest blockhash: {err}"))?;
-
-    tx.try_sign(&[signer], blockhash)
-        .map_err(|err| format!("error: failed to sign transaction: {err}"))?;
+        .map(|report| {
+            report
+                .with_account("mint", mint_pubkey)
+                .with_account("sourceTokenAccount", signer_ata)
+                .with_account("destinationTokenAccount", receiver_ata)
+                .with_amount(amount)
+        })
+}
 
-    let config = RpcSendTransactionConfig {
-        skip_preflight: true,
-        ..RpcSendTransactionConfig::default()
+/// Transfers from the associated token account of `owner` as its delegate, or as the
+/// permanent delegate of the mint. The source account is checked first unless `sign_only`,
+/// which leaves it to the token program.
+#[allow(clippy::too_many_arguments)]
+pub async fn process_transfer_from(
+    rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
+    signer: &dyn Signer,
+    fee_payer: &dyn Signer,
+    mint_pubkey: Pubkey,
+    owner_pubkey: Pubkey,
+    receiver_pubkey: Pubkey,
+    amount: &Amount,
+    token_program: Option<TokenProgram>,
+    mint_decimals: Option<u8>,
+) -> Result<TransactionReport, Box<dyn std::error::Error>> {
+    let token_program = resolve_token_program(rpc_client, token_program, &mint_pubkey).await?;
+    let supply = if tx_config.sign_only {
+        None
+    } else {
+        Some(get_mint_supply(rpc_client, &mint_pubkey).await?)
     };
+    let decimals = match (mint_decimals, &supply) {
+        (Some(decimals), _) => decimals,
+        (None, Some((supply, _))) => supply.decimals,
+        (None, None) => return Err("error: --sign-only requires --mint-decimals".into()),
+    };
+    let amount = amount
+        .to_token_amount(decimals)
+        .map_err(|err| format!("error: {err}"))?;
+    let receiver_ata = get_associated_token_address_with_program_id(
+        &receiver_pubkey,
+        &mint_pubkey,
+        &token_program,
+    );
+    let owner_ata =
+        get_associated_token_address_with_program_id(&owner_pubkey, &mint_pubkey, &token_program);
 
-    let signature = rpc_client
-        .send_transaction_with_config(&tx, config)
-        .await
-        .map_err(|err| format!("error: send transaction: {err}"))?;
+    if let Some((_, permanent_delegate)) = supply {
+        check_source_account(
+            rpc_client,
+            &owner_ata,
+            &mint_pubkey,
+            &signer.pubkey(),
+            permanent_delegate,
+            &amount,
+        )
+        .await?;
+    }
 
-    Ok(signature)
+    let transfer_ixs = transfer_checked_instructions(
+        &signer.pubkey(),
+        &owner_pubkey,
+        &fee_payer.pubkey(),
+        &mint_pubkey,
+        &receiver_pubkey,
+        amount.amount,
+        amount.decimals,
+        &token_program,
+    )?;
+
+    execute_transaction(rpc_client, tx_config, &transfer_ixs, fee_payer, &[signer])
+        .await
+        .map(|report| {
+            report
+                .with_account("mint", mint_pubkey)
+                .with_account("sourceTokenAccount", owner_ata)
+                .with_account("destinationTokenAccount", receiver_ata)
+                .with_amount(amount)
+        })
 }
@@ -1,21 +1,29 @@
 use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_client::rpc_config::RpcSendTransactionConfig;
-use solana_sdk::message::Message;
 use solana_sdk::pubkey::Pubkey;
-use solana_sdk::transaction::Transaction;
-use solana_sdk::{signature::Signature, signer::Signer};
-use spl_associated_token_account::get_associated_token_address;
+use solana_sdk::signer::Signer;
+use spl_associated_token_account::get_associated_token_address_with_program_id;
+
+use crate::token_program::{resolve_token_program, TokenProgram};
+use crate::transaction::{execute_transaction, TransactionConfig, TransactionReport};
 
 pub async fn process_unfreeze_account(
     rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
     signer: &dyn Signer,
+    fee_payer: &dyn Signer,
     mint_pubkey: Pubkey,
     receiver_pubkey: Pubkey,
-) -> Result<Signature, Box<dyn std::error::Error>> {
-    let receiver_ata = get_associated_token_address(&receiver_pubkey, &mint_pubkey);
+    token_program: Option<TokenProgram>,
+) -> Result<TransactionReport, Box<dyn std::error::Error>> {
+    let token_program = resolve_token_program(rpc_client, token_program, &mint_pubkey).await?;
+    let receiver_ata = get_associated_token_address_with_program_id(
+        &receiver_pubkey,
+        &mint_pubkey,
+        &token_program,
+    );
 
-    let unfreeze_ix = spl_token::instruction::thaw_account(
-        &spl_token::ID,
+    let unfreeze_ix = spl_token_2022::instruction::thaw_account(
+        &token_program,
         &receiver_ata,
         &mint_pubkey,
         &signer.pubkey(),
@@ -23,25 +31,11 @@ pub async fn process_unfreeze_account(
     )
     .unwrap();
 
-    let mut tx = Transaction::new_unsigned(Message::new(&[unfreeze_ix], Some(&signer.pubkey())));
-
-    let blockhash = rpc_client
-        .get_latest_blockhash()
-        .await
-        .map_err(|err| format!("error: unable to get latest blockhash: {err}"))?;
-
-    tx.try_sign(&[signer], blockhash)
-        .map_err(|err| format!("error: failed to sign transaction: {err}"))?;
-
-    let config = RpcSendTransactionConfig {
-        skip_preflight: true,
-        ..RpcSendTransactionConfig::default()
-    };
-
-    let signature = rpc_client
-        .send_transaction_with_config(&tx, config)
+    execute_transaction(rpc_client, tx_config, &[unfreeze_ix], fee_payer, &[signer])
         .await
-        .map_err(|err| format!("error: send transaction: {err}"))?;
-
-    Ok(signature)
+        .map(|report| {
+            report
+                .with_account("mint", mint_pubkey)
+                .with_account("tokenAccount", receiver_ata)
+        })
 }
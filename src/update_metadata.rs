@@ -1,38 +1,895 @@
-use mpl_token_metadata::instructions::UpdateMetadataAccountV2Builder;
+use std::str::FromStr;
+
+use dialoguer::{Confirm, Input};
+use mpl_token_metadata::accounts::{MasterEdition, Metadata};
+use mpl_token_metadata::instructions::{
+    UpdateAsUpdateAuthorityV2Builder, UpdateMetadataAccountV2Builder,
+};
+use mpl_token_metadata::types::{
+    Collection, CollectionToggle, Creator, Data, DataV2, RuleSetToggle, TokenStandard, UseMethod,
+    Uses, UsesToggle,
+};
+use mpl_token_metadata::MAX_CREATOR_LIMIT;
+use serde::Deserialize;
 use solana_client::nonblocking::rpc_client::RpcClient;
-use solana_client::rpc_config::RpcSendTransactionConfig;
-use solana_sdk::message::Message;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+
+use crate::metadata::{rule_set_of, MPL_TOKEN_AUTH_RULES_ID};
+use crate::transaction::{execute_transaction, TransactionConfig, TransactionReport};
+use crate::utils::{from_str, from_str_option, from_str_vec};
+
+/// Creator entry of a metadata patch. Verification is kept from the current creators and
+/// cleared for new ones.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreatorPatch {
+    #[serde(deserialize_with = "from_str")]
+    pub address: Pubkey,
+    pub share: u8,
+}
+
+impl FromStr for CreatorPatch {
+    type Err = String;
+
+    /// Parses `ADDRESS:SHARE`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, share) = s
+            .split_once(':')
+            .ok_or_else(|| format!("invalid creator `{s}`, expected ADDRESS:SHARE"))?;
+        Ok(CreatorPatch {
+            address: address
+                .parse()
+                .map_err(|_| format!("invalid creator address `{address}`"))?,
+            share: share
+                .parse()
+                .map_err(|_| format!("invalid creator share `{share}`"))?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UsesPatch {
+    #[serde(deserialize_with = "from_str")]
+    pub use_method: UseMethodArg,
+    pub remaining: u64,
+    pub total: u64,
+}
+
+impl FromStr for UsesPatch {
+    type Err = String;
+
+    /// Parses `METHOD:REMAINING:TOTAL`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split(':').collect();
+        let [use_method, remaining, total] = parts[..] else {
+            return Err(format!(
+                "invalid uses `{s}`, expected METHOD:REMAINING:TOTAL"
+            ));
+        };
+        Ok(UsesPatch {
+            use_method: use_method.parse()?,
+            remaining: remaining
+                .parse()
+                .map_err(|_| format!("invalid remaining uses `{remaining}`"))?,
+            total: total
+                .parse()
+                .map_err(|_| format!("invalid total uses `{total}`"))?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UseMethodArg {
+    Burn,
+    Multiple,
+    Single,
+}
+
+impl FromStr for UseMethodArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "burn" => Ok(UseMethodArg::Burn),
+            "multiple" => Ok(UseMethodArg::Multiple),
+            "single" => Ok(UseMethodArg::Single),
+            _ => Err(format!(
+                "invalid use method `{s}`, expected burn, multiple or single"
+            )),
+        }
+    }
+}
+
+impl From<UseMethodArg> for UseMethod {
+    fn from(method: UseMethodArg) -> Self {
+        match method {
+            UseMethodArg::Burn => UseMethod::Burn,
+            UseMethodArg::Multiple => UseMethod::Multiple,
+            UseMethodArg::Single => UseMethod::Single,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TokenStandardArg(pub TokenStandard);
+
+impl FromStr for TokenStandardArg {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "fungible" => Ok(TokenStandardArg(TokenStandard::Fungible)),
+            "fungible_asset" => Ok(TokenStandardArg(TokenStandard::FungibleAsset)),
+            "non_fungible" => Ok(TokenStandardArg(TokenStandard::NonFungible)),
+            "non_fungible_edition" => Ok(TokenStandardArg(TokenStandard::NonFungibleEdition)),
+            "programmable_non_fungible" => {
+                Ok(TokenStandardArg(TokenStandard::ProgrammableNonFungible))
+            }
+            "programmable_non_fungible_edition" => Ok(TokenStandardArg(
+                TokenStandard::ProgrammableNonFungibleEdition,
+            )),
+            _ => Err(format!(
+                "invalid token standard `{s}`, expected fungible, fungible_asset, non_fungible, \
+                 non_fungible_edition, programmable_non_fungible or \
+                 programmable_non_fungible_edition"
+            )),
+        }
+    }
+}
+
+/// Changes to a token's metadata, read from `--from-file` and the `updateMetadata` flags.
+/// Fields left out keep their on-chain value.
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetadataPatch {
+    pub name: Option<String>,
+    pub symbol: Option<String>,
+    pub uri: Option<String>,
+    pub seller_fee_basis_points: Option<u16>,
+    /// Replaces the whole creator list; an empty list removes the creators.
+    pub creators: Option<Vec<CreatorPatch>>,
+    /// Creators appended to the list, or whose share changes when already in it.
+    pub add_creators: Vec<CreatorPatch>,
+    #[serde(deserialize_with = "from_str_vec")]
+    pub remove_creators: Vec<Pubkey>,
+    /// New order of the creators, naming each of them once.
+    #[serde(deserialize_with = "from_str_vec")]
+    pub creator_order: Vec<Pubkey>,
+    #[serde(deserialize_with = "from_str_option")]
+    pub collection: Option<Pubkey>,
+    pub clear_collection: bool,
+    pub uses: Option<UsesPatch>,
+    pub clear_uses: bool,
+    pub is_mutable: Option<bool>,
+    #[serde(deserialize_with = "from_str_option")]
+    pub new_update_authority: Option<Pubkey>,
+    /// Can only be set, never reset.
+    pub primary_sale_happened: Option<bool>,
+    #[serde(deserialize_with = "from_str_option")]
+    pub token_standard: Option<TokenStandardArg>,
+    /// Authorization rule set of a programmable NFT.
+    #[serde(deserialize_with = "from_str_option")]
+    pub rule_set: Option<Pubkey>,
+    pub clear_rule_set: bool,
+}
+
+impl MetadataPatch {
+    fn changes_data(&self) -> bool {
+        self.name.is_some()
+            || self.symbol.is_some()
+            || self.uri.is_some()
+            || self.seller_fee_basis_points.is_some()
+            || self.changes_creators()
+            || self.collection.is_some()
+            || self.clear_collection
+            || self.uses.is_some()
+            || self.clear_uses
+    }
+
+    fn changes_creators(&self) -> bool {
+        self.creators.is_some()
+            || !self.add_creators.is_empty()
+            || !self.remove_creators.is_empty()
+            || !self.creator_order.is_empty()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        !self.changes_data()
+            && self.is_mutable.is_none()
+            && self.new_update_authority.is_none()
+            && self.primary_sale_happened.is_none()
+            && self.token_standard.is_none()
+            && self.rule_set.is_none()
+            && !self.clear_rule_set
+    }
+
+    /// Merges the patch into the current metadata.
+    pub fn apply(&self, metadata: &Metadata) -> Result<MetadataUpdate, String> {
+        if self.collection.is_some() && self.clear_collection {
+            return Err("a collection cannot be both set and cleared".to_string());
+        }
+        if self.uses.is_some() && self.clear_uses {
+            return Err("uses cannot be both set and cleared".to_string());
+        }
+        if self.rule_set.is_some() && self.clear_rule_set {
+            return Err("a rule set cannot be both set and cleared".to_string());
+        }
+        if metadata.primary_sale_happened && self.primary_sale_happened == Some(false) {
+            return Err("the primary sale has happened and cannot be reset".to_string());
+        }
+        let changes_mutable_fields = self.changes_data()
+            || self.is_mutable == Some(true)
+            || self.primary_sale_happened.is_some()
+            || self.token_standard.is_some()
+            || self.rule_set.is_some()
+            || self.clear_rule_set;
+        if !metadata.is_mutable && changes_mutable_fields {
+            return Err("metadata is immutable, only the update authority can change".to_string());
+        }
+
+        let creators = if self.changes_creators() {
+            self.merge_creators(metadata)?
+        } else {
+            metadata.creators.clone()
+        };
+
+        let data = self.changes_data().then(|| {
+            let collection = match self.collection {
+                Some(key) => Some(Collection {
+                    verified: metadata
+                        .collection
+                        .as_ref()
+                        .is_some_and(|collection| collection.key == key && collection.verified),
+                    key,
+                }),
+                None if self.clear_collection => None,
+                None => metadata.collection.clone(),
+            };
+            let uses = match &self.uses {
+                Some(uses) => Some(Uses {
+                    use_method: uses.use_method.into(),
+                    remaining: uses.remaining,
+                    total: uses.total,
+                }),
+                None if self.clear_uses => None,
+                None => metadata.uses.clone(),
+            };
+
+            DataV2 {
+                name: self.name.clone().unwrap_or_else(|| metadata.name.clone()),
+                symbol: self
+                    .symbol
+                    .clone()
+                    .unwrap_or_else(|| metadata.symbol.clone()),
+                uri: self.uri.clone().unwrap_or_else(|| metadata.uri.clone()),
+                seller_fee_basis_points: self
+                    .seller_fee_basis_points
+                    .unwrap_or(metadata.seller_fee_basis_points),
+                creators,
+                collection,
+                uses,
+            }
+        });
+
+        let rule_set = match self.rule_set {
+            Some(rule_set) => Some(Some(rule_set)),
+            None if self.clear_rule_set => Some(None),
+            None => None,
+        };
+
+        Ok(MetadataUpdate {
+            data,
+            is_mutable: self.is_mutable,
+            new_update_authority: self.new_update_authority,
+            primary_sale_happened: self.primary_sale_happened,
+            token_standard: self.token_standard.map(|standard| standard.0),
+            rule_set,
+        })
+    }
+
+    /// Applies the creator replacement, removals, additions and order in that sequence, and
+    /// checks the result.
+    fn merge_creators(&self, metadata: &Metadata) -> Result<Option<Vec<Creator>>, String> {
+        let mut creators: Vec<Creator> = match &self.creators {
+            Some(creators) => creators
+                .iter()
+                .map(|creator| Creator {
+                    address: creator.address,
+                    verified: is_verified_creator(metadata, &creator.address),
+                    share: creator.share,
+                })
+                .collect(),
+            None => metadata.creators.clone().unwrap_or_default(),
+        };
+
+        for address in &self.remove_creators {
+            let len = creators.len();
+            creators.retain(|creator| creator.address != *address);
+            if creators.len() == len {
+                return Err(format!("{address} is not a creator"));
+            }
+        }
+
+        for added in &self.add_creators {
+            match creators
+                .iter_mut()
+                .find(|creator| creator.address == added.address)
+            {
+                Some(creator) => creator.share = added.share,
+                None => creators.push(Creator {
+                    address: added.address,
+                    verified: is_verified_creator(metadata, &added.address),
+                    share: added.share,
+                }),
+            }
+        }
+
+        if !self.creator_order.is_empty() {
+            let mut ordered = Vec::with_capacity(creators.len());
+            for address in &self.creator_order {
+                let position = creators
+                    .iter()
+                    .position(|creator| creator.address == *address)
+                    .ok_or_else(|| format!("{address} is not a creator or is listed twice"))?;
+                ordered.push(creators.remove(position));
+            }
+            if let Some(creator) = creators.first() {
+                return Err(format!(
+                    "creator order is missing {}, it must name every creator",
+                    creator.address
+                ));
+            }
+            creators = ordered;
+        }
+
+        if creators.is_empty() {
+            return Ok(None);
+        }
+        if creators.len() > MAX_CREATOR_LIMIT {
+            return Err(format!("at most {MAX_CREATOR_LIMIT} creators are allowed"));
+        }
+        for (i, creator) in creators.iter().enumerate() {
+            if creators[..i]
+                .iter()
+                .any(|other| other.address == creator.address)
+            {
+                return Err(format!("creator {} is listed twice", creator.address));
+            }
+        }
+        let total: u32 = creators
+            .iter()
+            .map(|creator| u32::from(creator.share))
+            .sum();
+        if total != 100 {
+            return Err(format!("creator shares total {total}, they must total 100"));
+        }
+        Ok(Some(creators))
+    }
+}
+
+fn is_verified_creator(metadata: &Metadata, address: &Pubkey) -> bool {
+    metadata.creators.as_ref().is_some_and(|creators| {
+        creators
+            .iter()
+            .any(|creator| creator.address == *address && creator.verified)
+    })
+}
+
+/// Metadata fields to send in the update; `None` leaves a field unchanged.
+#[derive(Debug, Clone)]
+pub struct MetadataUpdate {
+    pub data: Option<DataV2>,
+    pub is_mutable: Option<bool>,
+    pub new_update_authority: Option<Pubkey>,
+    pub primary_sale_happened: Option<bool>,
+    pub token_standard: Option<TokenStandard>,
+    /// `Some(None)` clears the rule set.
+    pub rule_set: Option<Option<Pubkey>>,
+}
+
+impl MetadataUpdate {
+    /// Whether the update needs a field the legacy `UpdateMetadataAccountV2` cannot set.
+    fn needs_update_v1(&self) -> bool {
+        self.token_standard.is_some() || self.rule_set.is_some()
+    }
+}
+
+/// Returns the Token Metadata account of `mint` and its address.
+pub async fn get_metadata(
+    rpc_client: &RpcClient,
+    mint: &Pubkey,
+) -> Result<(Pubkey, Metadata), Box<dyn std::error::Error>> {
+    let (metadata_pubkey, _) = Metadata::find_pda(mint);
+    let data = rpc_client
+        .get_account_data(&metadata_pubkey)
+        .await
+        .map_err(|err| format!("error: unable to get metadata account {metadata_pubkey}: {err}"))?;
+
+    let metadata = Metadata::safe_deserialize(&data)
+        .map_err(|err| format!("error: invalid metadata account {metadata_pubkey}: {err}"))?;
+    Ok((metadata_pubkey, metadata))
+}
+
+/// Asks for the name, symbol, URI, seller fee, mutability and update authority in turn.
+pub fn prompt_metadata_patch() -> MetadataPatch {
+    let mut patch = MetadataPatch::default();
+
+    let confirm: bool = Confirm::new()
+        .with_prompt("Do you want to update token metadata?")
+        .interact()
+        .unwrap();
+
+    if confirm {
+        let name: String = Input::new()
+            .with_prompt("Name (leave blank to skip)")
+            .allow_empty(true)
+            .interact_text()
+            .unwrap();
+        if !name.is_empty() {
+            patch.name = Some(name);
+        }
+
+        let symbol: String = Input::new()
+            .with_prompt("Symbol (leave blank to skip)")
+            .allow_empty(true)
+            .interact_text()
+            .unwrap();
+        if !symbol.is_empty() {
+            patch.symbol = Some(symbol);
+        }
+
+        let uri: String = Input::new()
+            .with_prompt("URI (leave blank to skip)")
+            .allow_empty(true)
+            .interact_text()
+            .unwrap();
+        if !uri.is_empty() {
+            patch.uri = Some(uri);
+        }
+
+        let seller_fee_basis_points: String = Input::new()
+            .with_prompt("Seller fee basis points (leave blank to skip)")
+            .allow_empty(true)
+            .validate_with(|input: &String| {
+                if input.is_empty() || input.parse::<u16>().is_ok() {
+                    Ok(())
+                } else {
+                    Err("expected a number of basis points")
+                }
+            })
+            .interact_text()
+            .unwrap();
+        if !seller_fee_basis_points.is_empty() {
+            patch.seller_fee_basis_points = Some(seller_fee_basis_points.parse().unwrap());
+        }
+
+        let is_mutable: bool = Confirm::new()
+            .with_prompt("Is mutable? ")
+            .interact()
+            .unwrap();
+        patch.is_mutable = Some(is_mutable);
+    }
+
+    let confirm: bool = Confirm::new()
+        .with_prompt("Do you want to update the update authority?")
+        .interact()
+        .unwrap();
 
-use solana_sdk::transaction::Transaction;
-use solana_sdk::{signature::Signature, signer::Signer};
+    if confirm {
+        let update_authority: String = Input::new()
+            .with_prompt("Update authority address")
+            .validate_with(|input: &String| Pubkey::from_str(input).map(|_| ()))
+            .interact_text()
+            .unwrap();
+        patch.new_update_authority = Some(update_authority.parse().unwrap());
+    }
 
+    patch
+}
+
+/// Legacy instruction, which metadata accounts created before token standards need.
+fn update_metadata_account_v2_instruction(
+    authority: &Pubkey,
+    metadata_pubkey: Pubkey,
+    update: &MetadataUpdate,
+) -> Instruction {
+    let mut tx_builder = UpdateMetadataAccountV2Builder::new();
+    tx_builder
+        .metadata(metadata_pubkey)
+        .update_authority(*authority);
+    if let Some(data) = &update.data {
+        tx_builder.data(data.clone());
+    }
+    if let Some(is_mutable) = update.is_mutable {
+        tx_builder.is_mutable(is_mutable);
+    }
+    if let Some(new_update_authority) = update.new_update_authority {
+        tx_builder.new_update_authority(new_update_authority);
+    }
+    if let Some(primary_sale_happened) = update.primary_sale_happened {
+        tx_builder.primary_sale_happened(primary_sale_happened);
+    }
+    tx_builder.instruction()
+}
+
+/// `Update` instruction signed by the update authority, which covers every field. Collection
+/// and uses are sent as toggles, only when they change, so existing verifications are kept.
+/// The master edition is passed when `has_master_edition`, whatever the recorded token
+/// standard, since Token Metadata needs it to check a new one.
+fn update_as_update_authority_instruction(
+    authority: &Pubkey,
+    fee_payer: &Pubkey,
+    metadata_pubkey: Pubkey,
+    metadata: &Metadata,
+    has_master_edition: bool,
+    update: &MetadataUpdate,
+) -> Instruction {
+    let mut tx_builder = UpdateAsUpdateAuthorityV2Builder::new();
+    tx_builder
+        .authority(*authority)
+        .mint(metadata.mint)
+        .metadata(metadata_pubkey)
+        .payer(*fee_payer);
+
+    if has_master_edition {
+        let (edition, _) = MasterEdition::find_pda(&metadata.mint);
+        tx_builder.edition(Some(edition));
+    }
+    if let Some(rule_set) = rule_set_of(metadata) {
+        tx_builder
+            .authorization_rules_program(Some(MPL_TOKEN_AUTH_RULES_ID))
+            .authorization_rules(Some(rule_set));
+    }
+
+    if let Some(data) = &update.data {
+        tx_builder.data(Data {
+            name: data.name.clone(),
+            symbol: data.symbol.clone(),
+            uri: data.uri.clone(),
+            seller_fee_basis_points: data.seller_fee_basis_points,
+            creators: data.creators.clone(),
+        });
+        if data.collection != metadata.collection {
+            tx_builder.collection(match &data.collection {
+                Some(collection) => CollectionToggle::Set(collection.clone()),
+                None => CollectionToggle::Clear,
+            });
+        }
+        if data.uses != metadata.uses {
+            tx_builder.uses(match &data.uses {
+                Some(uses) => UsesToggle::Set(uses.clone()),
+                None => UsesToggle::Clear,
+            });
+        }
+    }
+    if let Some(rule_set) = update.rule_set {
+        tx_builder.rule_set(match rule_set {
+            Some(rule_set) => RuleSetToggle::Set(rule_set),
+            None => RuleSetToggle::Clear,
+        });
+    }
+    if let Some(token_standard) = update.token_standard {
+        tx_builder.token_standard(token_standard);
+    }
+    if let Some(is_mutable) = update.is_mutable {
+        tx_builder.is_mutable(is_mutable);
+    }
+    if let Some(new_update_authority) = update.new_update_authority {
+        tx_builder.new_update_authority(new_update_authority);
+    }
+    if let Some(primary_sale_happened) = update.primary_sale_happened {
+        tx_builder.primary_sale_happened(primary_sale_happened);
+    }
+    tx_builder.instruction()
+}
+
+/// Sends `update` with the Token Metadata `Update` instruction, or with the legacy
+/// `UpdateMetadataAccountV2` for metadata accounts without a token standard when it can
+/// express the update.
+#[allow(clippy::too_many_arguments)]
 pub async fn process_update_metadata(
     rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
     signer: &dyn Signer,
-    tx_builder: UpdateMetadataAccountV2Builder,
-) -> Result<Signature, Box<dyn std::error::Error>> {
-    let update_metadata_ix = tx_builder.instruction();
+    fee_payer: &dyn Signer,
+    metadata_pubkey: Pubkey,
+    metadata: &Metadata,
+    update: &MetadataUpdate,
+) -> Result<TransactionReport, Box<dyn std::error::Error>> {
+    let update_metadata_ix = if metadata.token_standard.is_none() && !update.needs_update_v1() {
+        update_metadata_account_v2_instruction(&signer.pubkey(), metadata_pubkey, update)
+    } else {
+        let (edition, _) = MasterEdition::find_pda(&metadata.mint);
+        let has_master_edition = rpc_client
+            .get_account_with_commitment(&edition, rpc_client.commitment())
+            .await
+            .map_err(|err| format!("error: unable to get master edition {edition}: {err}"))?
+            .value
+            .is_some();
+        update_as_update_authority_instruction(
+            &signer.pubkey(),
+            &fee_payer.pubkey(),
+            metadata_pubkey,
+            metadata,
+            has_master_edition,
+            update,
+        )
+    };
 
-    let mut tx =
-        Transaction::new_unsigned(Message::new(&[update_metadata_ix], Some(&signer.pubkey())));
+    execute_transaction(
+        rpc_client,
+        tx_config,
+        &[update_metadata_ix],
+        fee_payer,
+        &[signer],
+    )
+    .await
+}
 
-    let blockhash = rpc_client
-        .get_latest_blockhash()
-        .await
-        .map_err(|err| format!("error: unable to get latest blockhash: {err}"))?;
+#[cfg(test)]
+mod tests {
+    use mpl_token_metadata::types::{Key, TokenStandard};
 
-    tx.try_sign(&[signer], blockhash)
-        .map_err(|err| format!("error: failed to sign transaction: {err}"))?;
+    use super::*;
 
-    let config = RpcSendTransactionConfig {
-        skip_preflight: true,
-        ..RpcSendTransactionConfig::default()
-    };
+    fn metadata() -> Metadata {
+        Metadata {
+            key: Key::MetadataV1,
+            update_authority: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            name: "Token".to_string(),
+            symbol: "TKN".to_string(),
+            uri: "https://example.com/token.json".to_string(),
+            seller_fee_basis_points: 0,
+            creators: None,
+            primary_sale_happened: false,
+            is_mutable: true,
+            edition_nonce: None,
+            token_standard: Some(TokenStandard::Fungible),
+            collection: None,
+            uses: None,
+            collection_details: None,
+            programmable_config: None,
+        }
+    }
 
-    let signature = rpc_client
-        .send_transaction_with_config(&tx, config)
-        .await
-        .map_err(|err| format!("error: send transaction: {err}"))?;
+    #[test]
+    fn parses_a_toml_patch() {
+        let (creator, collection) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let patch: MetadataPatch = toml::from_str(&format!(
+            r#"
+            name = "Bridged Token"
+            collection = "{collection}"
+
+            [[creators]]
+            address = "{creator}"
+            share = 100
+
+            [uses]
+            use_method = "multiple"
+            remaining = 5
+            total = 10
+            "#
+        ))
+        .unwrap();
+
+        assert_eq!(patch.name.as_deref(), Some("Bridged Token"));
+        assert_eq!(patch.symbol, None);
+        assert_eq!(patch.collection, Some(collection));
+        assert_eq!(
+            patch.creators,
+            Some(vec![CreatorPatch {
+                address: creator,
+                share: 100
+            }])
+        );
+        assert_eq!(patch.uses.map(|uses| uses.remaining), Some(5));
+    }
+
+    #[test]
+    fn rejects_unknown_and_malformed_fields() {
+        assert!(toml::from_str::<MetadataPatch>("nmae = \"Token\"").is_err());
+        assert!(toml::from_str::<MetadataPatch>("collection = \"not-a-pubkey\"").is_err());
+        assert!(
+            serde_json::from_str::<MetadataPatch>(r#"{"uses": {"use_method": "burn"}}"#).is_err()
+        );
+    }
+
+    #[test]
+    fn apply_keeps_fields_left_out() {
+        let metadata = metadata();
+        let patch = MetadataPatch {
+            name: Some("Renamed".to_string()),
+            ..MetadataPatch::default()
+        };
+        let data = patch.apply(&metadata).unwrap().data.unwrap();
+
+        assert_eq!(data.name, "Renamed");
+        assert_eq!(data.symbol, metadata.symbol);
+        assert_eq!(data.uri, metadata.uri);
+    }
+
+    #[test]
+    fn apply_only_changes_the_update_authority_of_immutable_metadata() {
+        let metadata = Metadata {
+            is_mutable: false,
+            ..metadata()
+        };
+        let rename = MetadataPatch {
+            name: Some("Renamed".to_string()),
+            ..MetadataPatch::default()
+        };
+        let make_mutable = MetadataPatch {
+            is_mutable: Some(true),
+            ..MetadataPatch::default()
+        };
+        let new_authority = MetadataPatch {
+            new_update_authority: Some(Pubkey::new_unique()),
+            ..MetadataPatch::default()
+        };
+
+        let token_standard = MetadataPatch {
+            token_standard: Some(TokenStandardArg(TokenStandard::FungibleAsset)),
+            ..MetadataPatch::default()
+        };
+        let rule_set = MetadataPatch {
+            clear_rule_set: true,
+            ..MetadataPatch::default()
+        };
+        let primary_sale = MetadataPatch {
+            primary_sale_happened: Some(true),
+            ..MetadataPatch::default()
+        };
+
+        assert!(rename.apply(&metadata).is_err());
+        assert!(make_mutable.apply(&metadata).is_err());
+        assert!(token_standard.apply(&metadata).is_err());
+        assert!(rule_set.apply(&metadata).is_err());
+        assert!(primary_sale.apply(&metadata).is_err());
+        assert!(new_authority.apply(&metadata).is_ok());
+    }
+
+    #[test]
+    fn passes_the_master_edition_whenever_it_exists() {
+        let metadata = Metadata {
+            token_standard: None,
+            ..metadata()
+        };
+        let update = MetadataPatch {
+            token_standard: Some(TokenStandardArg(TokenStandard::NonFungible)),
+            ..MetadataPatch::default()
+        }
+        .apply(&metadata)
+        .unwrap();
+        let (edition, _) = MasterEdition::find_pda(&metadata.mint);
+        let accounts_of = |has_master_edition| {
+            update_as_update_authority_instruction(
+                &Pubkey::new_unique(),
+                &Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                &metadata,
+                has_master_edition,
+                &update,
+            )
+            .accounts
+        };
+
+        assert!(accounts_of(true)
+            .iter()
+            .any(|account| account.pubkey == edition));
+        assert!(!accounts_of(false)
+            .iter()
+            .any(|account| account.pubkey == edition));
+    }
+
+    #[test]
+    fn apply_keeps_the_collection_verification_for_the_same_key() {
+        let key = Pubkey::new_unique();
+        let metadata = Metadata {
+            collection: Some(Collection {
+                verified: true,
+                key,
+            }),
+            ..metadata()
+        };
+        let same = MetadataPatch {
+            collection: Some(key),
+            ..MetadataPatch::default()
+        };
+        let other = MetadataPatch {
+            collection: Some(Pubkey::new_unique()),
+            ..MetadataPatch::default()
+        };
+
+        let data_after = |patch: MetadataPatch| patch.apply(&metadata).unwrap().data.unwrap();
+        assert!(data_after(same).collection.unwrap().verified);
+        assert!(!data_after(other).collection.unwrap().verified);
+    }
+
+    fn creator(address: Pubkey, share: u8, verified: bool) -> Creator {
+        Creator {
+            address,
+            verified,
+            share,
+        }
+    }
+
+    fn patch(address: Pubkey, share: u8) -> CreatorPatch {
+        CreatorPatch { address, share }
+    }
+
+    #[test]
+    fn removes_before_adding_and_keeps_verifications() {
+        let (old, new) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let metadata = Metadata {
+            creators: Some(vec![creator(old, 100, true)]),
+            ..metadata()
+        };
+        let patch = MetadataPatch {
+            remove_creators: vec![old],
+            add_creators: vec![patch(old, 40), patch(new, 60)],
+            ..MetadataPatch::default()
+        };
+
+        assert_eq!(
+            patch.merge_creators(&metadata),
+            Ok(Some(vec![creator(old, 40, true), creator(new, 60, false)]))
+        );
+    }
+
+    #[test]
+    fn creator_order_must_name_every_creator_once() {
+        let (a, b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let metadata = Metadata {
+            creators: Some(vec![creator(a, 50, false), creator(b, 50, false)]),
+            ..metadata()
+        };
+        let orders = [vec![a], vec![a, a], vec![a, b, Pubkey::new_unique()]];
+
+        for creator_order in orders {
+            let patch = MetadataPatch {
+                creator_order,
+                ..MetadataPatch::default()
+            };
+            assert!(patch.merge_creators(&metadata).is_err());
+        }
+    }
+
+    #[test]
+    fn shares_must_total_100() {
+        let patch = MetadataPatch {
+            creators: Some(vec![
+                patch(Pubkey::new_unique(), 60),
+                patch(Pubkey::new_unique(), 30),
+            ]),
+            ..MetadataPatch::default()
+        };
+
+        let err = patch.merge_creators(&metadata()).unwrap_err();
+        assert!(err.contains("total 90"), "{err}");
+    }
+
+    #[test]
+    fn rejects_more_than_the_creator_limit_and_duplicates() {
+        let too_many = MetadataPatch {
+            creators: Some(
+                (0..=MAX_CREATOR_LIMIT)
+                    .map(|i| patch(Pubkey::new_unique(), if i == 0 { 95 } else { 1 }))
+                    .collect(),
+            ),
+            ..MetadataPatch::default()
+        };
+        let a = Pubkey::new_unique();
+        let duplicate = MetadataPatch {
+            creators: Some(vec![patch(a, 50), patch(a, 50)]),
+            ..MetadataPatch::default()
+        };
 
-    Ok(signature)
+        assert!(too_many.merge_creators(&metadata()).is_err());
+        let err = duplicate.merge_creators(&metadata()).unwrap_err();
+        assert!(err.contains("listed twice"), "{err}");
+    }
 }
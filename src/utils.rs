@@ -1,4 +1,12 @@
+use std::fmt::Display;
+use std::fs::OpenOptions;
+use std::path::Path;
+use std::str::FromStr;
+
 use anchor_client::anchor_lang;
+use serde::de::{DeserializeOwned, Error as _};
+use serde::{Deserialize, Deserializer};
+use solana_sdk::signature::{write_keypair, Keypair};
 
 pub fn get_function_hash(namespace: &str, name: &str) -> [u8; 8] {
     let preimage = format!("{}:{}", namespace, name);
@@ -8,3 +16,76 @@ pub fn get_function_hash(namespace: &str, name: &str) -> [u8; 8] {
     );
     sighash
 }
+
+/// Writes `keypair` to a new file at `path` in the Solana CLI JSON format, refusing to overwrite
+/// an existing file.
+pub fn write_keypair_file_new(
+    keypair: &Keypair,
+    path: &str,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+
+    let mut file = options
+        .open(path)
+        .map_err(|err| format!("error: unable to create keypair file {path}: {err}"))?;
+    write_keypair(keypair, &mut file)
+        .map_err(|err| format!("error: unable to write keypair file {path}: {err}"))?;
+    Ok(())
+}
+
+/// Reads a JSON or TOML file into `T`, picking the format from the file extension.
+pub fn read_config_file<T: DeserializeOwned>(path: &str) -> Result<T, Box<dyn std::error::Error>> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|err| format!("error: unable to read {path}: {err}"))?;
+
+    if Path::new(path).extension().is_some_and(|ext| ext == "toml") {
+        toml::from_str(&contents)
+            .map_err(|err| format!("error: invalid TOML in {path}: {err}").into())
+    } else {
+        serde_json::from_str(&contents)
+            .map_err(|err| format!("error: invalid JSON in {path}: {err}").into())
+    }
+}
+
+/// Deserializes a value from its string form, so pubkeys can be written as base58.
+pub fn from_str<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: FromStr,
+    T::Err: Display,
+    D: Deserializer<'de>,
+{
+    String::deserialize(deserializer)?
+        .parse()
+        .map_err(D::Error::custom)
+}
+
+/// Deserializes an optional value from its string form, so pubkeys can be written as base58.
+pub fn from_str_option<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: FromStr,
+    T::Err: Display,
+    D: Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer)?
+        .map(|s| s.parse().map_err(D::Error::custom))
+        .transpose()
+}
+
+/// Deserializes a list of values from their string forms.
+pub fn from_str_vec<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    T: FromStr,
+    T::Err: Display,
+    D: Deserializer<'de>,
+{
+    Vec::<String>::deserialize(deserializer)?
+        .iter()
+        .map(|s| s.parse().map_err(D::Error::custom))
+        .collect()
+}
@@ -0,0 +1,100 @@
+use mpl_token_metadata::accounts::{MasterEdition, Metadata};
+use mpl_token_metadata::instructions::{
+    RemoveCreatorVerificationBuilder, SignMetadataBuilder, UnverifyCollectionV1Builder,
+    VerifyCollectionV1Builder,
+};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signer::Signer;
+
+use crate::transaction::{execute_transaction, TransactionConfig, TransactionReport};
+use crate::update_metadata::get_metadata;
+
+/// Marks `signer` as a verified creator of the token, or removes that verification.
+pub async fn process_sign_metadata(
+    rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
+    signer: &dyn Signer,
+    fee_payer: &dyn Signer,
+    mint_pubkey: Pubkey,
+    verify: bool,
+) -> Result<TransactionReport, Box<dyn std::error::Error>> {
+    let (metadata_pubkey, metadata) = get_metadata(rpc_client, &mint_pubkey).await?;
+    let is_creator = metadata.creators.as_ref().is_some_and(|creators| {
+        creators
+            .iter()
+            .any(|creator| creator.address == signer.pubkey())
+    });
+    if !is_creator {
+        return Err(format!(
+            "error: {} is not a creator of {mint_pubkey}",
+            signer.pubkey()
+        )
+        .into());
+    }
+
+    let sign_ix = if verify {
+        SignMetadataBuilder::new()
+            .metadata(metadata_pubkey)
+            .creator(signer.pubkey())
+            .instruction()
+    } else {
+        RemoveCreatorVerificationBuilder::new()
+            .metadata(metadata_pubkey)
+            .creator(signer.pubkey())
+            .instruction()
+    };
+
+    execute_transaction(rpc_client, tx_config, &[sign_ix], fee_payer, &[signer])
+        .await
+        .map(|report| {
+            report
+                .with_account("mint", mint_pubkey)
+                .with_account("metadata", metadata_pubkey)
+                .with_account("creator", signer.pubkey())
+        })
+}
+
+/// Verifies the token as a member of the collection set in its metadata, or unverifies it.
+/// The signer must be the update authority of the collection.
+pub async fn process_verify_collection(
+    rpc_client: &RpcClient,
+    tx_config: &TransactionConfig,
+    signer: &dyn Signer,
+    fee_payer: &dyn Signer,
+    mint_pubkey: Pubkey,
+    verify: bool,
+) -> Result<TransactionReport, Box<dyn std::error::Error>> {
+    let (metadata_pubkey, metadata) = get_metadata(rpc_client, &mint_pubkey).await?;
+    let collection = metadata
+        .collection
+        .ok_or_else(|| format!("error: {mint_pubkey} has no collection"))?;
+    let (collection_metadata, _) = Metadata::find_pda(&collection.key);
+
+    let verify_ix = if verify {
+        let (collection_master_edition, _) = MasterEdition::find_pda(&collection.key);
+        VerifyCollectionV1Builder::new()
+            .authority(signer.pubkey())
+            .metadata(metadata_pubkey)
+            .collection_mint(collection.key)
+            .collection_metadata(Some(collection_metadata))
+            .collection_master_edition(Some(collection_master_edition))
+            .instruction()
+    } else {
+        UnverifyCollectionV1Builder::new()
+            .authority(signer.pubkey())
+            .metadata(metadata_pubkey)
+            .collection_mint(collection.key)
+            .collection_metadata(Some(collection_metadata))
+            .instruction()
+    };
+
+    execute_transaction(rpc_client, tx_config, &[verify_ix], fee_payer, &[signer])
+        .await
+        .map(|report| {
+            report
+                .with_account("mint", mint_pubkey)
+                .with_account("metadata", metadata_pubkey)
+                .with_account("collection", collection.key)
+        })
+}